@@ -0,0 +1,47 @@
+//! # Errors for chain registry
+
+use serde_json;
+use std::{error, fmt, io};
+
+/// Chain registry errors
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// Can't read chains config
+    IO(String),
+
+    /// Invalid chains config
+    InvalidConfig(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::IO(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::InvalidConfig(err.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::IO(ref str) => write!(f, "Chains config IO error: {}", str),
+            Error::InvalidConfig(ref str) => write!(f, "Invalid chains config: {}", str),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        "Chain registry error"
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            _ => None,
+        }
+    }
+}
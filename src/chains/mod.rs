@@ -0,0 +1,349 @@
+//! # Registry of supported chains
+//!
+//! Built-in chains can be extended or overridden with a JSON config file:
+//!
+//! ```json
+//! [{"name": "mordor", "chain_id": 63, "aliases": ["etc-mordor"]}]
+//! ```
+//!
+mod error;
+
+pub use self::error::Error;
+use crate::core::Address;
+use crate::mnemonic::{hd_path, HDPath};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+
+/// Address checksum scheme used by a chain
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumScheme {
+    /// Mixed-case checksum, [EIP-55](https://eips.ethereum.org/EIPS/eip-55)
+    Eip55,
+
+    /// Chain id aware checksum, [EIP-1191](https://eips.ethereum.org/EIPS/eip-1191)
+    Eip1191,
+}
+
+impl Default for ChecksumScheme {
+    fn default() -> Self {
+        ChecksumScheme::Eip55
+    }
+}
+
+/// Chain description
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChainInfo {
+    /// Chain name, used also as storage subfolder
+    pub name: String,
+
+    /// Numerical chain id (EIP-155)
+    pub chain_id: u64,
+
+    /// Alternative names for the chain
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    /// Default HD path for mnemonic accounts (without address index),
+    /// SLIP-44 based path is used if omitted
+    #[serde(default)]
+    pub hd_path: String,
+
+    /// Address checksum scheme
+    #[serde(default)]
+    pub checksum: ChecksumScheme,
+}
+
+impl ChainInfo {
+    fn new(name: &str, chain_id: u64, aliases: &[&str], checksum: ChecksumScheme) -> ChainInfo {
+        ChainInfo {
+            name: name.to_string(),
+            chain_id,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            hd_path: String::new(),
+            checksum,
+        }
+        .with_default_hd_path()
+    }
+
+    fn with_default_hd_path(mut self) -> ChainInfo {
+        if self.hd_path.is_empty() {
+            self.hd_path = hd_path::default_base_path(self.chain_id, 0)
+                .expect("Expect valid default HD path")
+                .to_string();
+        }
+        self
+    }
+
+    /// Check whether chain is known by specified name or alias
+    ///
+    /// # Arguments:
+    ///
+    /// * name - chain name or alias, case insensitive
+    ///
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.name == name || self.aliases.iter().any(|a| *a == name)
+    }
+
+    /// Default HD path for mnemonic account of the chain,
+    /// with specified account and address indexes
+    ///
+    /// # Arguments:
+    ///
+    /// * account - account index (hardened)
+    /// * index - address index
+    ///
+    pub fn default_path(&self, account: u32, index: u32) -> Result<HDPath, crate::mnemonic::Error> {
+        HDPath::try_from(&self.hd_path)?
+            .with_account(account)?
+            .child(index)
+    }
+
+    /// Render address with checksum scheme of the chain
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - address to render
+    ///
+    pub fn format_address(&self, addr: &Address) -> String {
+        match self.checksum {
+            ChecksumScheme::Eip55 => addr.to_checksum(None),
+            ChecksumScheme::Eip1191 => addr.to_checksum(Some(self.chain_id)),
+        }
+    }
+}
+
+/// Registry of supported chains
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    chains: Vec<ChainInfo>,
+}
+
+impl ChainRegistry {
+    /// Load registry from JSON config file.
+    /// Chains from the config are added to built-in chains,
+    /// replacing built-in chain with the same name or chain id.
+    ///
+    /// # Arguments:
+    ///
+    /// * path - path to JSON file with array of `ChainInfo`
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ChainRegistry, Error> {
+        let f = File::open(path)?;
+        let chains: Vec<ChainInfo> = serde_json::from_reader(f)?;
+
+        let mut registry = ChainRegistry::default();
+        for chain in chains {
+            registry.add(chain)?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Add chain to registry, replacing chain with the same name or chain id
+    ///
+    /// # Arguments:
+    ///
+    /// * chain - chain description
+    ///
+    fn add(&mut self, mut chain: ChainInfo) -> Result<(), Error> {
+        chain.name = chain.name.to_lowercase();
+        chain.aliases = chain.aliases.iter().map(|a| a.to_lowercase()).collect();
+        if chain.name.is_empty() {
+            return Err(Error::InvalidConfig("Empty chain name".to_string()));
+        }
+        if !chain.hd_path.is_empty() && HDPath::try_from(&chain.hd_path).is_err() {
+            return Err(Error::InvalidConfig(format!(
+                "Invalid HD path for chain {}: {}",
+                chain.name, chain.hd_path
+            )));
+        }
+
+        self.chains
+            .retain(|c| c.name != chain.name && c.chain_id != chain.chain_id);
+        if let Some(c) = self
+            .chains
+            .iter()
+            .find(|c| c.is_named(&chain.name) || chain.aliases.iter().any(|a| c.is_named(a)))
+        {
+            return Err(Error::InvalidConfig(format!(
+                "Chain {} conflicts with aliases of chain {}",
+                chain.name, c.name
+            )));
+        }
+
+        self.chains.push(chain.with_default_hd_path());
+
+        Ok(())
+    }
+
+    /// Find chain by name or alias
+    ///
+    /// # Arguments:
+    ///
+    /// * name - chain name or alias, case insensitive
+    ///
+    pub fn find(&self, name: &str) -> Option<&ChainInfo> {
+        self.chains.iter().find(|c| c.is_named(name))
+    }
+
+    /// Find chain by numerical id
+    ///
+    /// # Arguments:
+    ///
+    /// * id - chain id
+    ///
+    pub fn find_by_id(&self, id: u64) -> Option<&ChainInfo> {
+        self.chains.iter().find(|c| c.chain_id == id)
+    }
+
+    /// List all registered chains
+    pub fn list(&self) -> &[ChainInfo] {
+        &self.chains
+    }
+}
+
+impl Default for ChainRegistry {
+    fn default() -> Self {
+        use self::ChecksumScheme::*;
+
+        ChainRegistry {
+            chains: vec![
+                ChainInfo::new("eth", 1, &[], Eip55),
+                ChainInfo::new("morden", 2, &[], Eip55),
+                ChainInfo::new("ropsten", 3, &[], Eip55),
+                ChainInfo::new("rinkeby", 4, &[], Eip55),
+                ChainInfo::new("rootstock-main", 30, &[], Eip1191),
+                ChainInfo::new("rootstock-test", 31, &[], Eip1191),
+                ChainInfo::new("kovan", 42, &[], Eip55),
+                ChainInfo::new("etc", 61, &["mainnet"], Eip55),
+                ChainInfo::new("etc-morden", 62, &[], Eip55),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_find_builtin_chain() {
+        let registry = ChainRegistry::default();
+        let id = |name| registry.find(name).map(|c| c.chain_id);
+
+        assert_eq!(id("eth"), Some(1));
+        assert_eq!(id("morden"), Some(2));
+        assert_eq!(id("ropsten"), Some(3));
+        assert_eq!(id("rinkeby"), Some(4));
+        assert_eq!(id("rootstock-main"), Some(30));
+        assert_eq!(id("rootstock-test"), Some(31));
+        assert_eq!(id("kovan"), Some(42));
+        assert_eq!(id("etc"), Some(61));
+        assert_eq!(id("mainnet"), Some(61));
+        assert_eq!(id("etc-morden"), Some(62));
+
+        assert_eq!(id("eTc"), Some(61));
+        assert_eq!(id("ecccc"), None);
+    }
+
+    #[test]
+    fn should_find_builtin_chain_by_id() {
+        let registry = ChainRegistry::default();
+        let name = |id| registry.find_by_id(id).map(|c| c.name.as_str());
+
+        assert_eq!(name(1), Some("eth"));
+        assert_eq!(name(2), Some("morden"));
+        assert_eq!(name(3), Some("ropsten"));
+        assert_eq!(name(4), Some("rinkeby"));
+        assert_eq!(name(30), Some("rootstock-main"));
+        assert_eq!(name(31), Some("rootstock-test"));
+        assert_eq!(name(42), Some("kovan"));
+        assert_eq!(name(61), Some("etc"));
+        assert_eq!(name(62), Some("etc-morden"));
+
+        assert_eq!(name(100), None);
+    }
+
+    #[test]
+    fn should_build_default_hd_path() {
+        let registry = ChainRegistry::default();
+        let etc = registry.find("etc").unwrap();
+
+        assert_eq!(etc.hd_path, "m/44'/61'/0'/0");
+        assert_eq!(
+            etc.default_path(2, 5).unwrap().to_string(),
+            "m/44'/61'/2'/0/5"
+        );
+    }
+
+    #[test]
+    fn should_load_config() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("chains.json");
+        std::fs::write(
+            &path,
+            r#"[
+              {"name": "Mordor", "chain_id": 63, "aliases": ["etc-mordor"]},
+              {"name": "private", "chain_id": 100, "hd_path": "m/44'/1'/0'/0", "checksum": "eip1191"},
+              {"name": "eth", "chain_id": 1, "hd_path": "m/44'/60'/0'/1"}
+            ]"#,
+        )
+        .unwrap();
+
+        let registry = ChainRegistry::load(&path).unwrap();
+        assert_eq!(registry.list().len(), 11);
+
+        let mordor = registry.find("etc-mordor").unwrap();
+        assert_eq!(mordor.name, "mordor");
+        assert_eq!(mordor.hd_path, "m/44'/1'/0'/0");
+
+        let private = registry.find_by_id(100).unwrap();
+        assert_eq!(private.checksum, ChecksumScheme::Eip1191);
+
+        assert_eq!(registry.find("eth").unwrap().hd_path, "m/44'/60'/0'/1");
+    }
+
+    #[test]
+    fn should_format_address_with_chain_checksum() {
+        let registry = ChainRegistry::default();
+        let addr = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            .parse::<Address>()
+            .unwrap();
+
+        assert_eq!(
+            registry.find("etc").unwrap().format_address(&addr),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        assert_eq!(
+            registry
+                .find("rootstock-main")
+                .unwrap()
+                .format_address(&addr),
+            "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"
+        );
+    }
+
+    #[test]
+    fn should_load_chain_id_above_u8() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("chains.json");
+        std::fs::write(&path, r#"[{"name": "sepolia", "chain_id": 11155111}]"#).unwrap();
+
+        let registry = ChainRegistry::load(&path).unwrap();
+        assert_eq!(registry.find_by_id(11_155_111).unwrap().name, "sepolia");
+        assert!(registry.find_by_id(11_155_111 % 256).is_none());
+    }
+
+    #[test]
+    fn should_reject_alias_conflict() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("chains.json");
+        std::fs::write(&path, r#"[{"name": "mainnet", "chain_id": 100}]"#).unwrap();
+
+        assert!(ChainRegistry::load(&path).is_err());
+    }
+}
@@ -0,0 +1,628 @@
+//! # Decoding of function calls, return values and event logs
+
+use super::{Contract, Error};
+use crate::util::keccak256;
+use ethabi::param_type::{ParamType, Writer};
+use ethabi::{Event, Function, Param, Token};
+use hex;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Function call or return value decoded according to contract ABI
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+    /// Function name
+    pub name: String,
+
+    /// Function signature, i.e. `transfer(address,uint256)`
+    pub signature: String,
+
+    /// Decoded params
+    pub params: Vec<DecodedParam>,
+}
+
+/// Single decoded param
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedParam {
+    /// Param name, empty if not specified in ABI
+    pub name: String,
+
+    /// Param type
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Param value
+    pub value: Value,
+}
+
+/// Event log decoded according to contract ABI
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedEvent {
+    /// Event name
+    pub name: String,
+
+    /// Event signature, i.e. `Transfer(address,address,uint256)`
+    pub signature: String,
+
+    /// Decoded params, in order of declaration
+    pub params: Vec<DecodedEventParam>,
+}
+
+/// Single decoded event param
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedEventParam {
+    /// Param name, empty if not specified in ABI
+    pub name: String,
+
+    /// Param type
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Whether param is stored in topics
+    pub indexed: bool,
+
+    /// Param value, hash of the value for indexed params of dynamic types
+    pub value: Value,
+}
+
+impl Contract {
+    /// Decode call data, searching function by selector
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - call data, including 4 bytes of function selector
+    ///
+    pub fn decode_call(&self, data: &[u8]) -> Result<DecodedCall, Error> {
+        if data.len() < 4 {
+            return Err(Error::InvalidContract(
+                "Call data is shorter than function selector".to_string(),
+            ));
+        }
+
+        let f = match self.inner.functions().find(|f| selector(f) == data[..4]) {
+            Some(f) => f,
+            None => {
+                return Err(tuple_error(&self.tuple_calls, |s| {
+                    keccak256(s.as_bytes())[..4] == data[..4]
+                })
+                .unwrap_or_else(|| {
+                    Error::InvalidContract(format!(
+                        "Unknown selector: 0x{}",
+                        hex::encode(&data[..4])
+                    ))
+                }))
+            }
+        };
+
+        decode(f, &f.inputs, &data[4..])
+    }
+
+    /// Decode data returned by function call
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - function name
+    /// * `data` - returned data
+    ///
+    pub fn decode_output(&self, name: &str, data: &[u8]) -> Result<DecodedCall, Error> {
+        let prefix = format!("{}(", name);
+        if let Some(e) = tuple_error(&self.tuple_calls, |s| s.starts_with(&prefix)) {
+            return Err(e);
+        }
+
+        let f = self
+            .get_function(name.to_string())
+            .ok_or_else(|| Error::InvalidContract(format!("Unknown function: {}", name)))?;
+
+        decode(&f, &f.outputs, data)
+    }
+
+    /// Decode event log, searching event by signature hash in the first topic
+    ///
+    /// # Arguments
+    ///
+    /// * `topics` - log topics
+    /// * `data` - log data
+    ///
+    pub fn decode_log(&self, topics: &[Vec<u8>], data: &[u8]) -> Result<DecodedEvent, Error> {
+        let topic0 = topics
+            .first()
+            .ok_or_else(|| Error::InvalidContract("Missing event topic".to_string()))?;
+
+        let event = self
+            .inner
+            .events()
+            .filter(|e| !e.anonymous)
+            .find(|e| keccak256(event_signature(e).as_bytes())[..] == topic0[..]);
+        let event = match event {
+            Some(event) => event,
+            None => {
+                return Err(tuple_error(&self.tuple_events, |s| {
+                    keccak256(s.as_bytes())[..] == topic0[..]
+                })
+                .unwrap_or_else(|| {
+                    Error::InvalidContract(format!(
+                        "Unknown event topic: 0x{}",
+                        hex::encode(topic0)
+                    ))
+                }))
+            }
+        };
+
+        decode_event(event, &topics[1..], data)
+    }
+}
+
+/// Error for function or event with tuple params, found by its signature
+///
+/// # Arguments
+///
+/// * `signatures` - signatures of functions or events with tuple params
+/// * `matches` - predicate on signature
+///
+fn tuple_error<F>(signatures: &[String], matches: F) -> Option<Error>
+where
+    F: Fn(&str) -> bool,
+{
+    signatures
+        .iter()
+        .find(|s| matches(s.as_str()))
+        .map(|s| Error::InvalidContract(format!("Tuple decoding unsupported: {}", s)))
+}
+
+/// Signature of function or event, i.e. `transfer(address,uint256)`
+fn signature<'a, I>(name: &str, kinds: I) -> String
+where
+    I: IntoIterator<Item = &'a ParamType>,
+{
+    let types: Vec<String> = kinds.into_iter().map(Writer::write).collect();
+    format!("{}({})", name, types.join(","))
+}
+
+fn function_signature(f: &Function) -> String {
+    signature(&f.name, f.inputs.iter().map(|p| &p.kind))
+}
+
+fn event_signature(e: &Event) -> String {
+    signature(&e.name, e.inputs.iter().map(|p| &p.kind))
+}
+
+/// First 4 bytes of function signature hash
+fn selector(f: &Function) -> [u8; 4] {
+    let hash = keccak256(function_signature(f).as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Whether value of type is hashed when used as indexed event param
+fn is_dynamic(kind: &ParamType) -> bool {
+    match *kind {
+        ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+        ParamType::FixedArray(ref inner, _) => is_dynamic(inner),
+        _ => false,
+    }
+}
+
+fn decode_event(e: &Event, topics: &[Vec<u8>], data: &[u8]) -> Result<DecodedEvent, Error> {
+    let indexed = e.inputs.iter().filter(|p| p.indexed).count();
+    if topics.len() != indexed {
+        return Err(Error::InvalidContract(format!(
+            "Expected {} indexed params of {}, got {}",
+            indexed,
+            e.name,
+            topics.len()
+        )));
+    }
+
+    let kinds: Vec<ParamType> = e
+        .inputs
+        .iter()
+        .filter(|p| !p.indexed)
+        .map(|p| p.kind.clone())
+        .collect();
+    let mut values = decode_tokens(&kinds, data)?.into_iter();
+    let mut topics = topics.iter();
+
+    let mut params = Vec::with_capacity(e.inputs.len());
+    for p in &e.inputs {
+        let value = if p.indexed {
+            let topic = topics.next().expect("Expect topic for indexed param");
+            if is_dynamic(&p.kind) {
+                Value::String(format!("0x{}", hex::encode(topic)))
+            } else {
+                to_value(&decode_tokens(&[p.kind.clone()], topic)?[0])
+            }
+        } else {
+            to_value(&values.next().expect("Expect value for non-indexed param"))
+        };
+
+        params.push(DecodedEventParam {
+            name: p.name.clone(),
+            kind: Writer::write(&p.kind),
+            indexed: p.indexed,
+            value,
+        });
+    }
+
+    Ok(DecodedEvent {
+        name: e.name.clone(),
+        signature: event_signature(e),
+        params,
+    })
+}
+
+/// Decode ABI encoded data, checking every offset and length against data size first.
+/// `ethabi::decode` trusts them, so malformed data makes it allocate memory
+/// for billions of elements or slice out of bounds.
+///
+/// # Arguments
+///
+/// * `types` - types of encoded values
+/// * `data` - encoded values
+///
+fn decode_tokens(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, Error> {
+    if data.len() % 32 != 0 {
+        return Err(invalid_data("length is not a multiple of 32 bytes"));
+    }
+
+    let words: Vec<&[u8]> = data.chunks(32).collect();
+    // every decoded element takes at least one word of data at some nesting level,
+    // anything above that is only possible with tails shared by several elements
+    let depth = types.iter().map(nesting).max().unwrap_or(0);
+    let mut budget = (words.len() + types.len()) * (depth + 1);
+    let mut offset = 0;
+    for kind in types {
+        offset = check_param(kind, &words, offset, &mut budget)?;
+    }
+
+    Ok(ethabi::decode(types, data)?)
+}
+
+/// Walk encoded param the same way as `ethabi::decode`, without allocations.
+/// Returns offset of the next param.
+fn check_param(
+    kind: &ParamType,
+    words: &[&[u8]],
+    offset: usize,
+    budget: &mut usize,
+) -> Result<usize, Error> {
+    if *budget == 0 {
+        return Err(invalid_data("too many elements"));
+    }
+    *budget -= 1;
+
+    match *kind {
+        ParamType::FixedBytes(len) => {
+            let end = offset + (len + 31) / 32;
+            if end > words.len() {
+                return Err(invalid_data("value is out of data"));
+            }
+            Ok(end)
+        }
+        ParamType::Bytes | ParamType::String => {
+            let start = tail_offset(words, offset)?;
+            let len = read_u32(words, start)?;
+            if start + 1 + (len + 31) / 32 > words.len() {
+                return Err(invalid_data("length exceeds data"));
+            }
+            Ok(offset + 1)
+        }
+        ParamType::Array(ref inner) => {
+            let start = tail_offset(words, offset)?;
+            let len = read_u32(words, start)?;
+            let tail = &words[start + 1..];
+            if len > tail.len() {
+                return Err(invalid_data("length exceeds data"));
+            }
+            let mut pos = 0;
+            for _ in 0..len {
+                pos = check_param(inner, tail, pos, budget)?;
+            }
+            Ok(offset + 1)
+        }
+        ParamType::FixedArray(ref inner, len) if is_dynamic(inner) => {
+            let start = tail_offset(words, offset)?;
+            if start > words.len() {
+                return Err(invalid_data("offset exceeds data"));
+            }
+            let tail = &words[start..];
+            let mut pos = 0;
+            for _ in 0..len {
+                pos = check_param(inner, tail, pos, budget)?;
+            }
+            Ok(offset + 1)
+        }
+        ParamType::FixedArray(ref inner, len) => {
+            let mut pos = offset;
+            for _ in 0..len {
+                pos = check_param(inner, words, pos, budget)?;
+            }
+            Ok(pos)
+        }
+        _ => {
+            read_word(words, offset)?;
+            Ok(offset + 1)
+        }
+    }
+}
+
+/// Position of the word the offset stored at `pos` points to
+fn tail_offset(words: &[&[u8]], pos: usize) -> Result<usize, Error> {
+    Ok(read_u32(words, pos)? / 32)
+}
+
+/// Read word at `pos` as 32-bit number, as `ethabi` does for offsets and lengths
+fn read_u32(words: &[&[u8]], pos: usize) -> Result<usize, Error> {
+    let word = read_word(words, pos)?;
+    if word[..28].iter().any(|b| *b != 0) {
+        return Err(invalid_data("offset or length exceeds 32 bits"));
+    }
+
+    Ok(word[28..]
+        .iter()
+        .fold(0usize, |acc, b| (acc << 8) + *b as usize))
+}
+
+fn read_word<'a>(words: &[&'a [u8]], pos: usize) -> Result<&'a [u8], Error> {
+    words
+        .get(pos)
+        .cloned()
+        .ok_or_else(|| invalid_data("value is out of data"))
+}
+
+/// Number of nested array levels of type, including type itself
+fn nesting(kind: &ParamType) -> usize {
+    match *kind {
+        ParamType::Array(ref inner) | ParamType::FixedArray(ref inner, _) => 1 + nesting(inner),
+        _ => 1,
+    }
+}
+
+fn invalid_data(reason: &str) -> Error {
+    Error::InvalidContract(format!("Invalid encoded data: {}", reason))
+}
+
+fn decode(f: &Function, params: &[Param], data: &[u8]) -> Result<DecodedCall, Error> {
+    let types: Vec<ParamType> = params.iter().map(|p| p.kind.clone()).collect();
+    let tokens = decode_tokens(&types, data)?;
+
+    Ok(DecodedCall {
+        name: f.name.clone(),
+        signature: function_signature(f),
+        params: params
+            .iter()
+            .zip(tokens.iter())
+            .map(|(p, t)| DecodedParam {
+                name: p.name.clone(),
+                kind: Writer::write(&p.kind),
+                value: to_value(t),
+            })
+            .collect(),
+    })
+}
+
+/// Convert token into readable JSON value:
+/// numbers as decimal strings, addresses and bytes as `0x` prefixed hex
+fn to_value(token: &Token) -> Value {
+    match *token {
+        Token::Address(ref a) => Value::String(format!("0x{:x}", a)),
+        Token::FixedBytes(ref b) | Token::Bytes(ref b) => {
+            Value::String(format!("0x{}", hex::encode(b)))
+        }
+        Token::Uint(ref u) => Value::String(u.to_string()),
+        Token::Int(ref i) => {
+            if i.bit(255) {
+                // two's complement
+                let abs = (!*i).overflowing_add(1.into()).0;
+                Value::String(format!("-{}", abs))
+            } else {
+                Value::String(i.to_string())
+            }
+        }
+        Token::Bool(b) => Value::Bool(b),
+        Token::String(ref s) => Value::String(s.clone()),
+        Token::FixedArray(ref tokens) | Token::Array(ref tokens) => {
+            Value::Array(tokens.iter().map(to_value).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &[u8] = b"[{\"constant\":false,\"inputs\":[{\"name\":\"to\",\"type\":\"address\"},\
+                        {\"name\":\"value\",\"type\":\"uint256\"}],\"name\":\"transfer\",\"outputs\":\
+                        [{\"name\":\"success\",\"type\":\"bool\"}],\"payable\":false,\"type\":\
+                        \"function\"},{\"constant\":true,\"inputs\":[{\"name\":\"delta\",\"type\":\
+                        \"int256\"}],\"name\":\"adjust\",\"outputs\":[],\"payable\":false,\"type\":\
+                        \"function\"},{\"anonymous\":false,\"inputs\":[{\"indexed\":true,\"name\":\
+                        \"from\",\"type\":\"address\"},{\"indexed\":true,\"name\":\"memo\",\"type\":\
+                        \"string\"},{\"indexed\":false,\"name\":\"value\",\"type\":\"uint256\"}],\
+                        \"name\":\"Paid\",\"type\":\"event\"}]";
+
+    #[test]
+    fn should_decode_call() {
+        let contract = Contract::try_from(ABI).unwrap();
+        let data = contract
+            .serialize_call(
+                "transfer",
+                vec![
+                    "00000000000000000000000000000000000000ff".to_string(),
+                    "1000".to_string(),
+                ],
+            )
+            .unwrap();
+
+        let call = contract.decode_call(&data).unwrap();
+        assert_eq!(call.name, "transfer");
+        assert_eq!(call.signature, "transfer(address,uint256)");
+        assert_eq!(
+            call.params,
+            vec![
+                DecodedParam {
+                    name: "to".to_string(),
+                    kind: "address".to_string(),
+                    value: Value::String("0x00000000000000000000000000000000000000ff".to_string()),
+                },
+                DecodedParam {
+                    name: "value".to_string(),
+                    kind: "uint256".to_string(),
+                    value: Value::String("1000".to_string()),
+                },
+            ]
+        );
+        assert!(contract.decode_call(&[0, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn should_decode_negative_int() {
+        let contract = Contract::try_from(ABI).unwrap();
+        let mut data = keccak256(b"adjust(int256)")[..4].to_vec();
+        data.extend_from_slice(&[0xff; 31]);
+        data.push(0xfb);
+
+        let call = contract.decode_call(&data).unwrap();
+        assert_eq!(call.params[0].value, Value::String("-5".to_string()));
+    }
+
+    #[test]
+    fn should_decode_output() {
+        let contract = Contract::try_from(ABI).unwrap();
+        let mut data = [0u8; 32];
+        data[31] = 1;
+
+        let res = contract.decode_output("transfer", &data).unwrap();
+        assert_eq!(res.params[0].name, "success");
+        assert_eq!(res.params[0].value, Value::Bool(true));
+    }
+
+    const UNSAFE_ABI: &[u8] = b"[{\"constant\":false,\"inputs\":[{\"name\":\"ids\",\"type\":\
+                               \"uint256[]\"}],\"name\":\"batch\",\"outputs\":[],\"payable\":false,\
+                               \"type\":\"function\"},{\"constant\":false,\"inputs\":[{\"name\":\
+                               \"names\",\"type\":\"string[2]\"}],\"name\":\"pair\",\"outputs\":[],\
+                               \"payable\":false,\"type\":\"function\"},{\"constant\":false,\"inputs\":\
+                               [{\"name\":\"rows\",\"type\":\"uint256[][]\"}],\"name\":\"grid\",\
+                               \"outputs\":[],\"payable\":false,\"type\":\"function\"}]";
+
+    fn word(n: usize) -> Vec<u8> {
+        let mut w = vec![0u8; 32];
+        for (i, b) in w[24..].iter_mut().enumerate() {
+            *b = (n >> (8 * (7 - i))) as u8;
+        }
+        w
+    }
+
+    #[test]
+    fn should_reject_oversized_array_length() {
+        let contract = Contract::try_from(UNSAFE_ABI).unwrap();
+        let mut data = keccak256(b"batch(uint256[])")[..4].to_vec();
+        data.extend(word(0x20));
+        data.extend(word(0xffff_ffff));
+
+        let res = contract.decode_call(&data);
+        assert!(res.unwrap_err().to_string().contains("length exceeds data"));
+    }
+
+    #[test]
+    fn should_reject_out_of_range_fixed_array_offset() {
+        let contract = Contract::try_from(UNSAFE_ABI).unwrap();
+        let mut data = keccak256(b"pair(string[2])")[..4].to_vec();
+        data.extend(word(0x20 * 100));
+
+        let res = contract.decode_call(&data);
+        assert!(res.unwrap_err().to_string().contains("offset exceeds data"));
+    }
+
+    #[test]
+    fn should_reject_rows_sharing_tail() {
+        let contract = Contract::try_from(UNSAFE_ABI).unwrap();
+        let rows = 100;
+        let mut data = keccak256(b"grid(uint256[][])")[..4].to_vec();
+        data.extend(word(0x20));
+        data.extend(word(rows));
+        // every row points to the same inner array
+        for _ in 0..rows {
+            data.extend(word(rows * 32));
+        }
+        data.extend(word(rows));
+        for i in 0..rows {
+            data.extend(word(i));
+        }
+
+        let res = contract.decode_call(&data);
+        assert!(res.unwrap_err().to_string().contains("too many elements"));
+    }
+
+    #[test]
+    fn should_decode_nested_arrays() {
+        let contract = Contract::try_from(UNSAFE_ABI).unwrap();
+        let mut data = keccak256(b"grid(uint256[][])")[..4].to_vec();
+        data.extend(word(0x20));
+        data.extend(word(2));
+        data.extend(word(0x40));
+        data.extend(word(0x80));
+        data.extend(word(1));
+        data.extend(word(7));
+        data.extend(word(0));
+
+        let call = contract.decode_call(&data).unwrap();
+        assert_eq!(call.params[0].value, serde_json::json!([["7"], []]));
+    }
+
+    #[test]
+    fn should_reject_log_with_oversized_array_length() {
+        let abi = br#"[{"anonymous":false,"inputs":[{"indexed":false,"name":"ids",
+                      "type":"uint256[]"}],"name":"Batch","type":"event"}]"#;
+        let contract = Contract::try_from(abi).unwrap();
+        let topics = vec![keccak256(b"Batch(uint256[])").to_vec()];
+        let mut data = word(0x20);
+        data.extend(word(0xffff_ffff));
+
+        let res = contract.decode_log(&topics, &data);
+        assert!(res.unwrap_err().to_string().contains("length exceeds data"));
+    }
+
+    #[test]
+    fn should_reject_short_topic() {
+        let contract = Contract::try_from(ABI).unwrap();
+        let topics = vec![
+            keccak256(b"Paid(address,string,uint256)").to_vec(),
+            vec![0xff],
+            keccak256(b"invoice").to_vec(),
+        ];
+
+        assert!(contract.decode_log(&topics, &word(10)).is_err());
+    }
+
+    #[test]
+    fn should_decode_log() {
+        let contract = Contract::try_from(ABI).unwrap();
+        let mut from = vec![0u8; 32];
+        from[31] = 0xff;
+        let memo = keccak256(b"invoice").to_vec();
+        let mut data = vec![0u8; 32];
+        data[31] = 10;
+        let topics = vec![
+            keccak256(b"Paid(address,string,uint256)").to_vec(),
+            from,
+            memo.clone(),
+        ];
+
+        let event = contract.decode_log(&topics, &data).unwrap();
+        assert_eq!(event.name, "Paid");
+        assert_eq!(
+            event.params[0].value,
+            Value::String("0x00000000000000000000000000000000000000ff".to_string())
+        );
+        assert!(event.params[1].indexed);
+        assert_eq!(
+            event.params[1].value,
+            Value::String(format!("0x{}", hex::encode(&memo)))
+        );
+        assert!(!event.params[2].indexed);
+        assert_eq!(event.params[2].value, Value::String("10".to_string()));
+
+        assert!(contract.decode_log(&topics[..2], &data).is_err());
+        assert!(contract.decode_log(&[], &data).is_err());
+    }
+}
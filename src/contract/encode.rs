@@ -0,0 +1,426 @@
+//! # ABI encoding of tuples (ABIEncoderV2 structs) and nested arrays
+
+use super::Error;
+use crate::util::{keccak256, to_bytes, trim_hex};
+use ethabi::param_type::{ParamType, Reader, Writer};
+use ethabi::token::{LenientTokenizer, Tokenizer};
+use serde_json::Value;
+use std::fmt;
+
+/// ABI type, extending `ParamType` with tuples
+#[derive(Clone, Debug, PartialEq)]
+pub enum AbiType {
+    /// Type without arrays and tuples, i.e. `uint256`
+    Elementary(ParamType),
+
+    /// Dynamic array, i.e. `T[]`
+    Array(Box<AbiType>),
+
+    /// Fixed size array, i.e. `T[2]`
+    FixedArray(Box<AbiType>, usize),
+
+    /// Tuple components with names, empty if not specified
+    Tuple(Vec<(String, AbiType)>),
+}
+
+impl AbiType {
+    /// Parse type string, i.e. `(uint256,address)[]` or `tuple(uint256,bytes)`
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - type string
+    ///
+    pub fn parse(s: &str) -> Result<AbiType, Error> {
+        let s = s.trim();
+        if s.starts_with('(') || s.starts_with("tuple(") {
+            let start = s.find('(').expect("Expect opening parenthesis");
+            let end = closing_paren(s, start)?;
+            let components = split_components(&s[start + 1..end])
+                .into_iter()
+                .map(|c| Ok((String::new(), AbiType::parse(c)?)))
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            with_suffix(AbiType::Tuple(components), &s[end + 1..])
+        } else {
+            let end = s.find('[').unwrap_or_else(|| s.len());
+            with_suffix(AbiType::Elementary(Reader::read(&s[..end])?), &s[end..])
+        }
+    }
+
+    /// Parse type of JSON ABI param, with `components` for tuples
+    ///
+    /// # Arguments
+    ///
+    /// * `param` - JSON ABI param, i.e. `{"type": "tuple[]", "components": [...]}`
+    ///
+    pub fn from_param(param: &Value) -> Result<AbiType, Error> {
+        let kind = param
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::InvalidContract("Missing param type".to_string()))?;
+        if !kind.starts_with("tuple") || kind.starts_with("tuple(") {
+            return AbiType::parse(kind);
+        }
+
+        let components = param
+            .get("components")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error::InvalidContract(format!("Missing components of {}", kind)))?
+            .iter()
+            .map(|c| {
+                let name = c.get("name").and_then(Value::as_str).unwrap_or_default();
+                Ok((name.to_string(), AbiType::from_param(c)?))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        with_suffix(AbiType::Tuple(components), &kind["tuple".len()..])
+    }
+
+    /// Whether value is encoded in tail, referred by offset from head
+    pub fn is_dynamic(&self) -> bool {
+        match *self {
+            AbiType::Elementary(ParamType::Bytes)
+            | AbiType::Elementary(ParamType::String)
+            | AbiType::Array(_) => true,
+            AbiType::Elementary(_) => false,
+            AbiType::FixedArray(ref inner, _) => inner.is_dynamic(),
+            AbiType::Tuple(ref components) => components.iter().any(|(_, t)| t.is_dynamic()),
+        }
+    }
+
+    /// Convert to `ParamType`, if there are no tuples
+    fn to_param_type(&self) -> Option<ParamType> {
+        match *self {
+            AbiType::Elementary(ref kind) => Some(kind.clone()),
+            AbiType::Array(ref inner) => Some(ParamType::Array(Box::new(inner.to_param_type()?))),
+            AbiType::FixedArray(ref inner, n) => {
+                Some(ParamType::FixedArray(Box::new(inner.to_param_type()?), n))
+            }
+            AbiType::Tuple(_) => None,
+        }
+    }
+}
+
+impl From<ParamType> for AbiType {
+    fn from(kind: ParamType) -> Self {
+        match kind {
+            ParamType::Array(inner) => AbiType::Array(Box::new(AbiType::from(*inner))),
+            ParamType::FixedArray(inner, n) => {
+                AbiType::FixedArray(Box::new(AbiType::from(*inner)), n)
+            }
+            kind => AbiType::Elementary(kind),
+        }
+    }
+}
+
+impl fmt::Display for AbiType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AbiType::Elementary(ref kind) => write!(f, "{}", Writer::write(kind)),
+            AbiType::Array(ref inner) => write!(f, "{}[]", inner),
+            AbiType::FixedArray(ref inner, n) => write!(f, "{}[{}]", inner, n),
+            AbiType::Tuple(ref components) => {
+                let types: Vec<String> = components.iter().map(|(_, t)| t.to_string()).collect();
+                write!(f, "({})", types.join(","))
+            }
+        }
+    }
+}
+
+/// Position of parenthesis closing the one at `start`
+fn closing_paren(s: &str, start: usize) -> Result<usize, Error> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices().skip(start) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(Error::InvalidContract(format!(
+        "Unbalanced parentheses: {}",
+        s
+    )))
+}
+
+/// Split tuple components, separated by commas outside of nested tuples
+fn split_components(s: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                res.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        res.push(&s[start..]);
+    }
+
+    res
+}
+
+/// Apply array suffixes, i.e. `[2][]`, to base type
+fn with_suffix(base: AbiType, suffix: &str) -> Result<AbiType, Error> {
+    let mut kind = base;
+    let mut rest = suffix.trim();
+    while !rest.is_empty() {
+        let end = match rest.find(']') {
+            Some(end) if rest.starts_with('[') => end,
+            _ => {
+                return Err(Error::InvalidContract(format!(
+                    "Invalid type suffix: {}",
+                    suffix
+                )))
+            }
+        };
+        kind = match &rest[1..end] {
+            "" => AbiType::Array(Box::new(kind)),
+            n => {
+                let n = n
+                    .parse::<usize>()
+                    .map_err(|_| Error::InvalidContract(format!("Invalid array size: {}", n)))?;
+                AbiType::FixedArray(Box::new(kind), n)
+            }
+        };
+        rest = &rest[end + 1..];
+    }
+
+    Ok(kind)
+}
+
+/// Encode function call, including function selector
+///
+/// # Arguments
+///
+/// * `name` - function name
+/// * `types` - function input types
+/// * `values` - input params
+///
+pub fn encode_function(name: &str, types: &[AbiType], values: &[Value]) -> Result<Vec<u8>, Error> {
+    let types_str: Vec<String> = types.iter().map(AbiType::to_string).collect();
+    let signature = format!("{}({})", name, types_str.join(","));
+
+    let mut res = keccak256(signature.as_bytes())[..4].to_vec();
+    res.extend(encode(types, values)?);
+    Ok(res)
+}
+
+/// Encode sequence of values, as function params or tuple components
+///
+/// Tuples are given as JSON arrays or objects with component names as keys,
+/// arrays as JSON arrays. Other values are strings, numbers or booleans,
+/// same as accepted by `LenientTokenizer`.
+///
+/// # Arguments
+///
+/// * `types` - value types
+/// * `values` - values
+///
+pub fn encode(types: &[AbiType], values: &[Value]) -> Result<Vec<u8>, Error> {
+    if types.len() != values.len() {
+        return Err(Error::InvalidContract(format!(
+            "Expected {} params, got {}",
+            types.len(),
+            values.len()
+        )));
+    }
+
+    let encoded = types
+        .iter()
+        .zip(values.iter())
+        .map(|(kind, value)| encode_value(kind, value))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let head_len: usize = types
+        .iter()
+        .zip(encoded.iter())
+        .map(|(kind, data)| if kind.is_dynamic() { 32 } else { data.len() })
+        .sum();
+
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+    for (kind, data) in types.iter().zip(encoded.into_iter()) {
+        if kind.is_dynamic() {
+            head.extend_from_slice(&word(head_len + tail.len()));
+            tail.extend(data);
+        } else {
+            head.extend(data);
+        }
+    }
+    head.extend(tail);
+
+    Ok(head)
+}
+
+fn encode_value(kind: &AbiType, value: &Value) -> Result<Vec<u8>, Error> {
+    match (kind, value) {
+        (AbiType::Tuple(components), Value::Array(items)) => {
+            let types: Vec<AbiType> = components.iter().map(|(_, t)| t.clone()).collect();
+            encode(&types, items)
+        }
+        (AbiType::Tuple(components), Value::Object(fields)) => {
+            let types: Vec<AbiType> = components.iter().map(|(_, t)| t.clone()).collect();
+            let items = components
+                .iter()
+                .map(|(name, _)| {
+                    fields.get(name).cloned().ok_or_else(|| {
+                        Error::InvalidContract(format!("Missing tuple component: {}", name))
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            encode(&types, &items)
+        }
+        (AbiType::Array(inner), Value::Array(items)) => {
+            let mut res = word(items.len()).to_vec();
+            res.extend(encode(&vec![(**inner).clone(); items.len()], items)?);
+            Ok(res)
+        }
+        (AbiType::FixedArray(inner, n), Value::Array(items)) => {
+            if items.len() != *n {
+                return Err(Error::InvalidContract(format!(
+                    "Expected {} items of {}, got {}",
+                    n,
+                    kind,
+                    items.len()
+                )));
+            }
+            encode(&vec![(**inner).clone(); *n], items)
+        }
+        _ => encode_tokenized(kind, value),
+    }
+}
+
+/// Encode value given as a string, with `LenientTokenizer`
+fn encode_tokenized(kind: &AbiType, value: &Value) -> Result<Vec<u8>, Error> {
+    let param_type = kind
+        .to_param_type()
+        .ok_or_else(|| Error::InvalidContract(format!("Expected array or object for {}", kind)))?;
+    let value = match value {
+        Value::String(s) => match param_type {
+            ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+                trim_hex(s).to_string()
+            }
+            _ => s.clone(),
+        },
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => {
+            return Err(Error::InvalidContract(format!(
+                "Invalid value for {}: {}",
+                kind, value
+            )))
+        }
+    };
+
+    let token = LenientTokenizer::tokenize(&param_type, &value)?;
+    let data = ethabi::encode(&[token]);
+    if kind.is_dynamic() {
+        // skip offset of a single param
+        Ok(data[32..].to_vec())
+    } else {
+        Ok(data)
+    }
+}
+
+/// Big endian 32 bytes word of a number
+fn word(n: usize) -> [u8; 32] {
+    let mut res = [0u8; 32];
+    res[24..].copy_from_slice(&to_bytes(n as u64, 8));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex;
+    use serde_json::json;
+
+    #[test]
+    fn should_parse_types() {
+        let kind = AbiType::parse("tuple(uint256,(address,bytes)[2])[]").unwrap();
+        assert_eq!(kind.to_string(), "(uint256,(address,bytes)[2])[]");
+        assert!(kind.is_dynamic());
+        assert!(!AbiType::parse("(uint256,bool)[3]").unwrap().is_dynamic());
+        assert_eq!(
+            AbiType::parse("uint256[][2]").unwrap(),
+            AbiType::from(ParamType::FixedArray(
+                Box::new(ParamType::Array(Box::new(ParamType::Uint(256)))),
+                2
+            ))
+        );
+
+        let param = json!({
+            "name": "order",
+            "type": "tuple[]",
+            "components": [
+                { "name": "maker", "type": "address" },
+                { "name": "amount", "type": "uint256" }
+            ]
+        });
+        assert_eq!(
+            AbiType::from_param(&param).unwrap().to_string(),
+            "(address,uint256)[]"
+        );
+
+        assert!(AbiType::parse("(uint256").is_err());
+        assert!(AbiType::parse("uint256[x]").is_err());
+        assert!(AbiType::from_param(&json!({ "type": "tuple" })).is_err());
+    }
+
+    #[test]
+    fn should_encode_dynamic_tuple() {
+        let kind = AbiType::from_param(&json!({
+            "type": "tuple",
+            "components": [
+                { "name": "a", "type": "uint256" },
+                { "name": "b", "type": "bytes" }
+            ]
+        }))
+        .unwrap();
+        let expected = "4c94990d\
+                        0000000000000000000000000000000000000000000000000000000000000020\
+                        0000000000000000000000000000000000000000000000000000000000000001\
+                        0000000000000000000000000000000000000000000000000000000000000040\
+                        0000000000000000000000000000000000000000000000000000000000000002\
+                        dead000000000000000000000000000000000000000000000000000000000000";
+
+        let data = encode_function("f", &[kind.clone()], &[json!({ "a": 1, "b": "0xdead" })]);
+        assert_eq!(hex::encode(data.unwrap()), expected);
+        let data = encode_function("f", &[kind.clone()], &[json!(["1", "dead"])]);
+        assert_eq!(hex::encode(data.unwrap()), expected);
+
+        assert!(encode_function("f", &[kind.clone()], &[json!({ "a": 1 })]).is_err());
+        assert!(encode_function("f", &[kind], &[json!("1")]).is_err());
+    }
+
+    #[test]
+    fn should_encode_nested_arrays() {
+        let kind = AbiType::parse("uint256[][]").unwrap();
+        let data = encode(&[kind], &[json!([[1, 2], [3]])]).unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            "0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000040\
+             00000000000000000000000000000000000000000000000000000000000000a0\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000003"
+        );
+    }
+}
@@ -10,6 +10,12 @@ pub enum Error {
 
     /// Invalid Contract
     InvalidContract(String),
+
+    /// Contract is missing in storage
+    NotFound(String),
+
+    /// Another contract is stored for the same address
+    AlreadyExists(String),
 }
 
 impl fmt::Display for Error {
@@ -17,13 +23,23 @@ impl fmt::Display for Error {
         match *self {
             Error::IO(ref str) => write!(f, "IO error: {}", str),
             Error::InvalidContract(ref str) => write!(f, "Invalid contract: {}", str),
+            Error::NotFound(ref str) => write!(f, "Contract not found: {}", str),
+            Error::AlreadyExists(ref str) => {
+                write!(f, "Another contract already exists for address: {}", str)
+            }
         }
     }
 }
 
 impl From<ethabi::Error> for Error {
-    fn from(_: ethabi::Error) -> Self {
-        Error::InvalidContract("ethabi error".to_string())
+    fn from(err: ethabi::Error) -> Self {
+        Error::InvalidContract(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::InvalidContract(err.to_string())
     }
 }
 
@@ -0,0 +1,40 @@
+//! # Call data of contract factories
+
+use super::{Contract, Error};
+use hex;
+
+/// Encode factory `deploy(bytes32,bytes)` call, deploying contract with `CREATE2`
+///
+/// # Arguments
+///
+/// * `salt` - 32 bytes of salt
+/// * `bytecode` - contract init code
+///
+pub fn factory_deploy(salt: &[u8; 32], bytecode: &[u8]) -> Result<Vec<u8>, Error> {
+    Contract::serialize_signature_call(
+        "deploy",
+        &["bytes32".to_string(), "bytes".to_string()],
+        vec![hex::encode(salt), hex::encode(bytecode)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_encode_factory_deploy() {
+        let mut salt = [0u8; 32];
+        salt[31] = 1;
+        let data = factory_deploy(&salt, &[0xde, 0xad, 0xbe, 0xef]).unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            "cdcb760a\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000040\
+             0000000000000000000000000000000000000000000000000000000000000004\
+             deadbeef00000000000000000000000000000000000000000000000000000000"
+        );
+    }
+}
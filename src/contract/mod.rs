@@ -1,19 +1,39 @@
 //! # Contract
+mod decode;
+mod encode;
 #[allow(dead_code)]
 mod error;
+mod factory;
+mod token;
 
+pub use self::decode::{DecodedCall, DecodedEvent};
+pub use self::encode::AbiType;
 pub use self::error::Error;
-use ethabi::param_type::{ParamType, Reader};
-use ethabi::token::{LenientTokenizer, Token, Tokenizer};
+pub use self::factory::factory_deploy;
+pub use self::token::{erc20_transfer, erc721_transfer};
+use ethabi::token::Token;
 use ethabi::Function;
 use hex;
 use serde::Deserialize;
+use serde_json::Value;
 use std::fmt;
 
 /// Contract specification
 #[derive(Clone, Debug, Deserialize)]
 pub struct Contract {
     inner: ethabi::Contract,
+
+    /// Functions with tuple inputs, not supported by `ethabi`
+    #[serde(skip)]
+    tuple_functions: Vec<(String, Vec<AbiType>)>,
+
+    /// Signatures of functions with tuple inputs or outputs, which can't be decoded
+    #[serde(skip)]
+    tuple_calls: Vec<String>,
+
+    /// Signatures of events with tuple params, which can't be decoded
+    #[serde(skip)]
+    tuple_events: Vec<String>,
 }
 
 impl Contract {
@@ -23,49 +43,208 @@ impl Contract {
     ///
     /// * `DATA` - A byte slice
     ///
-    #[allow(dead_code)]
     pub fn try_from(data: &[u8]) -> Result<Self, Error> {
-        let inner = ethabi::Contract::load(data)?;
-        Ok(Contract { inner })
+        let entries: Vec<Value> = match serde_json::from_slice(data) {
+            Ok(entries) => entries,
+            Err(_) => {
+                let inner = ethabi::Contract::load(data)?;
+                return Ok(Contract {
+                    inner,
+                    tuple_functions: vec![],
+                    tuple_calls: vec![],
+                    tuple_events: vec![],
+                });
+            }
+        };
+
+        let mut supported = Vec::with_capacity(entries.len());
+        let mut tuple_functions = vec![];
+        let mut tuple_calls = vec![];
+        let mut tuple_events = vec![];
+        for mut entry in entries {
+            let tuple_inputs = has_tuple_params(&entry, "inputs");
+            let tuple_outputs = has_tuple_params(&entry, "outputs");
+            if !tuple_inputs && !tuple_outputs {
+                supported.push(entry);
+                continue;
+            }
+
+            let kind = entry
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or("function")
+                .to_string();
+            if kind != "function" && kind != "event" {
+                // constructor with tuple params
+                continue;
+            }
+
+            let name = entry
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| Error::InvalidContract(format!("Missing {} name", kind)))?
+                .to_string();
+            let types = entry
+                .get("inputs")
+                .and_then(Value::as_array)
+                .map_or(Ok(vec![]), |inputs| {
+                    inputs.iter().map(AbiType::from_param).collect()
+                })?;
+            let types_str: Vec<String> = types.iter().map(AbiType::to_string).collect();
+            let signature = format!("{}({})", name, types_str.join(","));
+
+            if kind == "event" {
+                tuple_events.push(signature);
+            } else if tuple_inputs {
+                tuple_calls.push(signature);
+                tuple_functions.push((name, types));
+            } else {
+                // outputs aren't needed to encode call
+                tuple_calls.push(signature);
+                entry["outputs"] = Value::Array(vec![]);
+                supported.push(entry);
+            }
+        }
+        let inner = ethabi::Contract::load(&serde_json::to_vec(&supported)?[..])?;
+
+        Ok(Contract {
+            inner,
+            tuple_functions,
+            tuple_calls,
+            tuple_events,
+        })
     }
 
     /// Returns specification of contract function given the function name.
-    #[allow(dead_code)]
     pub fn get_function(&self, name: String) -> Option<Function> {
         self.inner.function(&name).ok().cloned()
     }
 
-    /// Encode ABI function call with input params
-    #[allow(dead_code)]
+    /// Encode ABI function call with input params, including function selector
     pub fn serialize_function_call(
         &self,
         name: String,
         params: Vec<Token>,
     ) -> Result<Vec<u8>, Error> {
-        let f = self.get_function(name).unwrap();
+        let f = self
+            .get_function(name.clone())
+            .ok_or_else(|| Error::InvalidContract(format!("Unknown function: {}", name)))?;
         f.encode_input(&params).map_err(From::from)
     }
 
-    /// Encode ABI input params to hex string
-    pub fn serialize_params(types: &[String], values: Vec<String>) -> Result<String, Error> {
-        let types = types
+    /// Encode ABI function call with input params, converted according to function inputs.
+    /// Tuples are given as JSON arrays or objects, other params as strings.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - function name
+    /// * `values` - input params
+    ///
+    pub fn serialize_call<V: Into<Value>>(
+        &self,
+        name: &str,
+        values: Vec<V>,
+    ) -> Result<Vec<u8>, Error> {
+        let values: Vec<Value> = values.into_iter().map(Into::into).collect();
+
+        let mut overloads: Vec<Vec<AbiType>> = self
+            .tuple_functions
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, types)| types.clone())
+            .collect();
+        if let Some(f) = self.get_function(name.to_string()) {
+            overloads.push(
+                f.inputs
+                    .into_iter()
+                    .map(|p| AbiType::from(p.kind))
+                    .collect(),
+            );
+        }
+        if overloads.is_empty() {
+            return Err(Error::InvalidContract(format!(
+                "Unknown function: {}",
+                name
+            )));
+        }
+
+        let matching: Vec<&Vec<AbiType>> = overloads
             .iter()
-            .map(|s| Reader::read(s))
-            .collect::<Result<Vec<ParamType>, _>>()?;
+            .filter(|types| types.len() == values.len())
+            .collect();
+        let types = match matching.len() {
+            // reported as params count mismatch
+            0 => &overloads[0],
+            1 => matching[0],
+            _ => {
+                return Err(Error::InvalidContract(format!(
+                    "Ambiguous overloaded function {} with {} params",
+                    name,
+                    values.len()
+                )))
+            }
+        };
 
-        let params: Vec<_> = types.into_iter().zip(values.into_iter()).collect();
+        encode::encode_function(name, types, &values)
+    }
 
-        let tokens = params
+    /// Encode call of a function given by name and input types, without contract ABI
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - function name
+    /// * `types` - function input types, i.e. `(address,uint256)[]` for tuples
+    /// * `values` - input params
+    ///
+    pub fn serialize_signature_call<V: Into<Value>>(
+        name: &str,
+        types: &[String],
+        values: Vec<V>,
+    ) -> Result<Vec<u8>, Error> {
+        let types = types
             .iter()
-            .map(|&(ref param, ref value)| LenientTokenizer::tokenize(param, value))
+            .map(|s| AbiType::parse(s))
             .collect::<Result<Vec<_>, _>>()?;
+        let values: Vec<Value> = values.into_iter().map(Into::into).collect();
 
-        let result = ethabi::encode(&tokens);
+        encode::encode_function(name, &types, &values)
+    }
 
-        Ok(hex::encode(result))
+    /// Encode ABI input params to hex string
+    pub fn serialize_params<V: Into<Value>>(
+        types: &[String],
+        values: Vec<V>,
+    ) -> Result<String, Error> {
+        let types = types
+            .iter()
+            .map(|s| AbiType::parse(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        let values: Vec<Value> = values.into_iter().map(Into::into).collect();
+
+        Ok(hex::encode(encode::encode(&types, &values)?))
     }
 }
 
+/// Whether ABI entry has params of tuple type
+///
+/// # Arguments
+///
+/// * `entry` - ABI entry
+/// * `key` - params to check, `inputs` or `outputs`
+///
+fn has_tuple_params(entry: &Value, key: &str) -> bool {
+    entry
+        .get(key)
+        .and_then(Value::as_array)
+        .map_or(false, |params| {
+            params.iter().any(|p| {
+                p.get("type")
+                    .and_then(Value::as_str)
+                    .map_or(false, |t| t.starts_with("tuple") || t.starts_with('('))
+            })
+        })
+}
+
 impl fmt::Display for Contract {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self.inner)
@@ -76,6 +255,7 @@ impl fmt::Display for Contract {
 mod tests {
     use super::*;
     use ethabi::{Function, Param, ParamType};
+    use serde_json::json;
 
     #[test]
     fn should_display_contract_abi() {
@@ -108,4 +288,128 @@ mod tests {
         let f = contract.get_function("balanceOf".to_string()).unwrap();
         assert_eq!(f.inputs, function.inputs);
     }
+
+    #[test]
+    fn should_encode_call_with_selector() {
+        let c = b"[{\"constant\":false,\"inputs\":[{\"name\":\"to\",\"type\":\"address\"},\
+                 {\"name\":\"value\",\"type\":\"uint256\"}],\"name\":\"transfer\",\"outputs\":\
+                 [{\"name\":\"\",\"type\":\"bool\"}],\"payable\":false,\"type\":\"function\"}]";
+        let contract = Contract::try_from(c).unwrap();
+        let values = vec![
+            "0000000000000000000000000000000000000001".to_string(),
+            "10".to_string(),
+        ];
+
+        let data = contract.serialize_call("transfer", values.clone()).unwrap();
+        assert_eq!(
+            hex::encode(&data),
+            "a9059cbb\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             000000000000000000000000000000000000000000000000000000000000000a"
+        );
+
+        let types = vec!["address".to_string(), "uint256".to_string()];
+        assert_eq!(
+            Contract::serialize_signature_call("transfer", &types, values).unwrap(),
+            data
+        );
+        assert!(contract
+            .serialize_call("approve", Vec::<String>::new())
+            .is_err());
+        assert!(contract
+            .serialize_call("transfer", Vec::<String>::new())
+            .is_err());
+    }
+
+    #[test]
+    fn should_encode_call_with_struct() {
+        let c = b"[{\"constant\":false,\"inputs\":[{\"name\":\"orders\",\"type\":\"tuple[]\",\
+                 \"components\":[{\"name\":\"maker\",\"type\":\"address\"},{\"name\":\
+                 \"amount\",\"type\":\"uint256\"}]},{\"name\":\"fees\",\"type\":\"uint256[][]\"}],\
+                 \"name\":\"fill\",\"outputs\":[],\"payable\":false,\"type\":\"function\"},\
+                 {\"constant\":true,\"inputs\":[],\"name\":\"owner\",\"outputs\":[{\"name\":\"\",\
+                 \"type\":\"address\"}],\"payable\":false,\"type\":\"function\"}]";
+        let contract = Contract::try_from(c).unwrap();
+        let values = vec![
+            json!([{ "maker": "0x0000000000000000000000000000000000000001", "amount": "10" }]),
+            json!([[1], []]),
+        ];
+
+        let data = contract.serialize_call("fill", values.clone()).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "ac5481cd");
+        assert_eq!(
+            Contract::serialize_signature_call(
+                "fill",
+                &["(address,uint256)[]".to_string(), "uint256[][]".to_string()],
+                values
+            )
+            .unwrap(),
+            data
+        );
+        assert!(contract.get_function("owner".to_string()).is_some());
+    }
+
+    #[test]
+    fn should_keep_functions_with_tuple_outputs() {
+        let abi = json!([
+            {"type": "function", "name": "getOrder", "inputs": [{"name": "id", "type": "uint256"}],
+             "outputs": [{"name": "", "type": "tuple",
+                          "components": [{"name": "maker", "type": "address"}]}]},
+            {"type": "event", "name": "Filled", "anonymous": false,
+             "inputs": [{"name": "order", "type": "tuple", "indexed": false,
+                         "components": [{"name": "maker", "type": "address"}]}]}
+        ]);
+        let contract = Contract::try_from(&serde_json::to_vec(&abi).unwrap()).unwrap();
+
+        let data = contract.serialize_call("getOrder", vec!["1"]).unwrap();
+        assert_eq!(contract.decode_call(&data).unwrap().name, "getOrder");
+
+        let unsupported = |res: Result<_, Error>| match res {
+            Err(Error::InvalidContract(msg)) => msg.starts_with("Tuple decoding unsupported"),
+            _ => false,
+        };
+        assert!(unsupported(
+            contract.decode_output("getOrder", &[0; 32]).map(|_| ())
+        ));
+        let topic = crate::util::keccak256(b"Filled((address))").to_vec();
+        assert!(unsupported(
+            contract.decode_log(&[topic], &[0; 32]).map(|_| ())
+        ));
+    }
+
+    #[test]
+    fn should_select_tuple_overload_by_arity() {
+        let order = json!({"name": "order", "type": "tuple",
+                           "components": [{"name": "maker", "type": "address"}]});
+        let fee = json!({"name": "fee", "type": "uint256"});
+        let abi = json!([
+            {"type": "function", "name": "fill", "inputs": [order], "outputs": []},
+            {"type": "function", "name": "fill", "inputs": [order, fee], "outputs": []},
+            {"type": "function", "name": "cancel", "inputs": [order], "outputs": []},
+            {"type": "function", "name": "cancel", "inputs": [fee], "outputs": []}
+        ]);
+        let contract = Contract::try_from(&serde_json::to_vec(&abi).unwrap()).unwrap();
+        let maker = json!(["0x0000000000000000000000000000000000000001"]);
+
+        let data = contract
+            .serialize_call("fill", vec![maker.clone()])
+            .unwrap();
+        let types = ["(address)".to_string()];
+        assert_eq!(
+            Contract::serialize_signature_call("fill", &types, vec![maker.clone()]).unwrap(),
+            data
+        );
+
+        let data = contract
+            .serialize_call("fill", vec![maker.clone(), json!("1")])
+            .unwrap();
+        let types = ["(address)".to_string(), "uint256".to_string()];
+        assert_eq!(
+            Contract::serialize_signature_call("fill", &types, vec![maker.clone(), json!("1")])
+                .unwrap(),
+            data
+        );
+
+        assert!(contract.serialize_call("cancel", vec![maker]).is_err());
+    }
 }
@@ -0,0 +1,79 @@
+//! # Call data of standard token contracts
+
+use super::{Contract, Error};
+use crate::core::Address;
+
+/// Encode ERC-20 `transfer(address,uint256)` call
+///
+/// # Arguments
+///
+/// * `to` - recipient
+/// * `amount` - decimal number of token smallest units
+///
+pub fn erc20_transfer(to: &Address, amount: &str) -> Result<Vec<u8>, Error> {
+    Contract::serialize_signature_call(
+        "transfer",
+        &["address".to_string(), "uint256".to_string()],
+        vec![hex_address(to), amount.to_string()],
+    )
+}
+
+/// Encode ERC-721 `safeTransferFrom(address,address,uint256)` call
+///
+/// # Arguments
+///
+/// * `from` - current owner
+/// * `to` - recipient
+/// * `token_id` - decimal token id
+///
+pub fn erc721_transfer(from: &Address, to: &Address, token_id: &str) -> Result<Vec<u8>, Error> {
+    Contract::serialize_signature_call(
+        "safeTransferFrom",
+        &[
+            "address".to_string(),
+            "address".to_string(),
+            "uint256".to_string(),
+        ],
+        vec![hex_address(from), hex_address(to), token_id.to_string()],
+    )
+}
+
+/// Address hex without `0x` prefix, as expected by tokenizer
+fn hex_address(addr: &Address) -> String {
+    addr.to_string()[2..].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex;
+
+    fn addr(last: &str) -> Address {
+        format!("0x{:0>40}", last).parse().unwrap()
+    }
+
+    #[test]
+    fn should_encode_erc20_transfer() {
+        let data = erc20_transfer(&addr("ff"), "1000").unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            "a9059cbb\
+             00000000000000000000000000000000000000000000000000000000000000ff\
+             00000000000000000000000000000000000000000000000000000000000003e8"
+        );
+    }
+
+    #[test]
+    fn should_encode_erc721_transfer() {
+        let data = erc721_transfer(&addr("aa"), &addr("bb"), "7").unwrap();
+
+        assert_eq!(
+            hex::encode(data),
+            "42842e0e\
+             00000000000000000000000000000000000000000000000000000000000000aa\
+             00000000000000000000000000000000000000000000000000000000000000bb\
+             0000000000000000000000000000000000000000000000000000000000000007"
+        );
+    }
+}
@@ -1,8 +1,9 @@
 //! # Account address (20 bytes)
 
-use super::util::to_arr;
+use super::util::{keccak256, to_arr, RLPList, WriteRLP};
 use super::Error;
 use hex;
+use secp256k1::key::PublicKey;
 use std::str::FromStr;
 use std::{fmt, ops};
 
@@ -33,6 +34,77 @@ impl Address {
 
         Ok(Address(to_arr(data)))
     }
+
+    /// Extract `Address` from a public key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - secp256k1 public key
+    ///
+    pub fn from_public_key(key: &PublicKey) -> Self {
+        let hash = keccak256(&key.serialize_uncompressed()[1..] /* cut '04' */);
+        Address(to_arr(&hash[12..]))
+    }
+
+    /// Address of a contract deployed by this account with `CREATE`,
+    /// i.e. last 20 bytes of `keccak256(rlp([sender, nonce]))`
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - nonce of deployment transaction
+    ///
+    pub fn contract_address(&self, nonce: u64) -> Self {
+        let mut rlp = RLPList::default();
+        rlp.push(&self.0[..]);
+        rlp.push(&nonce);
+
+        let mut buf = Vec::new();
+        rlp.write_rlp(&mut buf);
+        let hash = keccak256(&buf);
+        Address(to_arr(&hash[12..]))
+    }
+
+    /// Address of a contract deployed by this account with `CREATE2`,
+    /// i.e. last 20 bytes of `keccak256(0xff ++ deployer ++ salt ++ keccak256(init_code))`
+    ///
+    /// # Arguments
+    ///
+    /// * `salt` - 32 bytes of salt
+    /// * `init_code_hash` - hash of contract init code
+    ///
+    pub fn create2_address(&self, salt: &[u8; 32], init_code_hash: &[u8; 32]) -> Self {
+        let mut buf = Vec::with_capacity(1 + ADDRESS_BYTES + 64);
+        buf.push(0xff);
+        buf.extend_from_slice(&self.0);
+        buf.extend_from_slice(salt);
+        buf.extend_from_slice(init_code_hash);
+
+        let hash = keccak256(&buf);
+        Address(to_arr(&hash[12..]))
+    }
+
+    /// Mixed-case checksum encoding, [EIP-55](https://eips.ethereum.org/EIPS/eip-55),
+    /// or [EIP-1191](https://eips.ethereum.org/EIPS/eip-1191) if chain id is specified
+    ///
+    /// # Arguments
+    ///
+    /// * `chain_id` - chain id for EIP-1191 checksum
+    ///
+    pub fn to_checksum(&self, chain_id: Option<u64>) -> String {
+        let lower = hex::encode(self.0);
+        let prefix = match chain_id {
+            Some(id) => format!("{}0x", id),
+            None => String::new(),
+        };
+        let hash = hex::encode(keccak256((prefix + &lower).as_bytes()));
+
+        let checksum: String = lower
+            .chars()
+            .zip(hash.chars())
+            .map(|(c, h)| if h >= '8' { c.to_ascii_uppercase() } else { c })
+            .collect();
+        format!("0x{}", checksum)
+    }
 }
 
 impl std::convert::AsRef<[u8]> for Address {
@@ -119,6 +191,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_predict_contract_address() {
+        let sender = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0"
+            .parse::<Address>()
+            .unwrap();
+
+        assert_eq!(
+            sender.contract_address(0).to_string(),
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"
+        );
+        assert_eq!(
+            sender.contract_address(1).to_string(),
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"
+        );
+    }
+
+    #[test]
+    fn should_compute_create2_address() {
+        let deployer = "0x00000000000000000000000000000000deadbeef"
+            .parse::<Address>()
+            .unwrap();
+        let mut salt = [0u8; 32];
+        salt[28..].copy_from_slice(&[0xca, 0xfe, 0xba, 0xbe]);
+
+        assert_eq!(
+            deployer
+                .create2_address(&salt, &keccak256(&[0xde, 0xad, 0xbe, 0xef]))
+                .to_string(),
+            "0x60f3f640a8508fc6a86d45df051962668e1e8ac7"
+        );
+        assert_eq!(
+            Address::default()
+                .create2_address(&[0u8; 32], &keccak256(&[0]))
+                .to_string(),
+            "0x4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"
+        );
+    }
+
     #[test]
     fn should_parse_real_address_without_prefix() {
         let addr = Address([
@@ -171,4 +281,22 @@ mod tests {
     fn should_catch_empty_address_string() {
         assert!("".parse::<Address>().is_err());
     }
+
+    #[test]
+    fn should_encode_checksum() {
+        let addr = Address::from_str("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+
+        assert_eq!(
+            addr.to_checksum(None),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+        assert_eq!(
+            addr.to_checksum(Some(30)),
+            "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"
+        );
+        assert_eq!(
+            addr.to_checksum(Some(31)),
+            "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd"
+        );
+    }
 }
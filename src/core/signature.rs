@@ -103,8 +103,7 @@ impl PrivateKey {
     /// Extract `Address` from current private key.
     pub fn to_address(self) -> Result<Address, Error> {
         let key = PublicKey::from_secret_key(&ECDSA, &self.into());
-        let hash = keccak256(&key.serialize_uncompressed()[1..] /* cut '04' */);
-        Ok(Address(to_arr(&hash[12..])))
+        Ok(Address::from_public_key(&key))
     }
 
     /// Sign message
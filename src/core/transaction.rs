@@ -27,25 +27,23 @@ pub struct Transaction {
 
 impl Transaction {
     /// Sign transaction data with provided private key
-    pub fn to_signed_raw(&self, pk: PrivateKey, chain: u8) -> Result<Vec<u8>, Error> {
+    pub fn to_signed_raw(&self, pk: PrivateKey, chain: u64) -> Result<Vec<u8>, Error> {
         let sig = pk.sign_hash(self.hash(chain))?;
         Ok(self.raw_from_sig(chain, &sig))
     }
 
     /// RLP packed signed transaction from provided `Signature`
-    pub fn raw_from_sig(&self, chain: u8, sig: &Signature) -> Vec<u8> {
+    pub fn raw_from_sig(&self, chain: u64, sig: &Signature) -> Vec<u8> {
         let mut rlp = self.to_rlp_raw(None);
 
         // [Simple replay attack protection](https://github.com/ethereum/eips/issues/155)
         // Can be already applied by HD wallet.
-        // TODO: refactor to avoid this check
-        let mut v = u16::from(sig.v);
-        let stamp = u16::from(chain * 2 + 35 - 27);
-        if v + stamp <= 0xff {
-            v += stamp;
+        let mut v = u64::from(sig.v);
+        if v == 27 || v == 28 {
+            v += chain * 2 + 35 - 27;
         }
 
-        rlp.push(&(v as u8));
+        rlp.push(&v);
         rlp.push(&sig.r[..]);
         rlp.push(&sig.s[..]);
 
@@ -56,15 +54,14 @@ impl Transaction {
     }
 
     /// RLP packed transaction
-    #[allow(dead_code)]
-    pub fn to_rlp(&self, chain_id: Option<u8>) -> Vec<u8> {
+    pub fn to_rlp(&self, chain_id: Option<u64>) -> Vec<u8> {
         let mut buf = Vec::new();
         self.to_rlp_raw(chain_id).write_rlp(&mut buf);
 
         buf
     }
 
-    fn to_rlp_raw(&self, chain_id: Option<u8>) -> RLPList {
+    fn to_rlp_raw(&self, chain_id: Option<u64>) -> RLPList {
         let mut data = RLPList::default();
 
         data.push(&self.nonce);
@@ -88,7 +85,8 @@ impl Transaction {
         data
     }
 
-    fn hash(&self, chain: u8) -> [u8; KECCAK256_BYTES] {
+    /// Hash of transaction data to be signed ([EIP-155](https://eips.ethereum.org/EIPS/eip-155))
+    pub fn hash(&self, chain: u64) -> [u8; KECCAK256_BYTES] {
         let rlp = self.to_rlp_raw(Some(chain));
         let mut vec = Vec::new();
         rlp.write_rlp(&mut vec);
@@ -245,4 +243,18 @@ mod tests {
              636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
         );
     }
+
+    #[test]
+    fn should_stamp_chain_id_above_u8() {
+        let sig = Signature {
+            v: 27,
+            r: [1; 32],
+            s: [2; 32],
+        };
+
+        let raw = Transaction::default().raw_from_sig(1000, &sig);
+        // v = 1000 * 2 + 35
+        let v = &raw[raw.len() - 66 - 3..raw.len() - 66];
+        assert_eq!(v, &[0x82, 0x07, 0xf3]);
+    }
 }
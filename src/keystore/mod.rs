@@ -139,27 +139,7 @@ impl KeyFile {
     /// Decrypt private key from keystore file by a password
     pub fn decrypt_key(&self, passphrase: &str) -> Result<PrivateKey, Error> {
         match self.crypto {
-            CryptoType::Core(ref core) => {
-                let derived = core.kdf_params.kdf.derive(
-                    core.kdf_params.dklen,
-                    &core.kdf_params.salt,
-                    passphrase,
-                );
-
-                let mut v = derived[16..32].to_vec();
-                v.extend_from_slice(&core.cipher_text);
-
-                let mac: [u8; KECCAK256_BYTES] = core.mac.into();
-                if keccak256(&v) != mac {
-                    return Err(Error::FailedMacValidation);
-                }
-
-                Ok(PrivateKey(to_arr(&core.cipher.encrypt(
-                    &core.cipher_text,
-                    &derived[0..16],
-                    &core.cipher_params.iv,
-                ))))
-            }
+            CryptoType::Core(ref core) => Ok(PrivateKey(to_arr(&core.decrypt(passphrase)?))),
         }
     }
 
@@ -173,33 +153,58 @@ impl KeyFile {
     /// and with given custom random generator
     pub fn encrypt_key_custom<R: Rng>(&mut self, pk: PrivateKey, passphrase: &str, rng: &mut R) {
         match self.crypto {
-            CryptoType::Core(ref mut core) => {
-                let mut buf_salt: [u8; KDF_SALT_BYTES] = [0; KDF_SALT_BYTES];
-                rng.fill_bytes(&mut buf_salt);
-                core.kdf_params.salt = Salt::from(buf_salt);
-
-                let derived = core.kdf_params.kdf.derive(
-                    core.kdf_params.dklen,
-                    &core.kdf_params.salt,
-                    passphrase,
-                );
-
-                let mut buf_iv: [u8; CIPHER_IV_BYTES] = [0; CIPHER_IV_BYTES];
-                rng.fill_bytes(&mut buf_iv);
-                core.cipher_params.iv = Iv::from(buf_iv);
-
-                core.cipher_text =
-                    core.cipher
-                        .encrypt(&pk, &derived[0..16], &core.cipher_params.iv);
-
-                let mut v = derived[16..32].to_vec();
-                v.extend_from_slice(&core.cipher_text);
-                core.mac = Mac::from(keccak256(&v));
-            }
+            CryptoType::Core(ref mut core) => core.encrypt_custom(&pk, passphrase, rng),
         }
     }
 }
 
+impl CoreCrypto {
+    /// Decrypt data by a passphrase
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, Error> {
+        let derived =
+            self.kdf_params
+                .kdf
+                .derive(self.kdf_params.dklen, &self.kdf_params.salt, passphrase);
+
+        let mut v = derived[16..32].to_vec();
+        v.extend_from_slice(&self.cipher_text);
+
+        let mac: [u8; KECCAK256_BYTES] = self.mac.into();
+        if keccak256(&v) != mac {
+            return Err(Error::FailedMacValidation);
+        }
+
+        Ok(self
+            .cipher
+            .encrypt(&self.cipher_text, &derived[0..16], &self.cipher_params.iv))
+    }
+
+    /// Encrypt data with a passphrase and with given custom random generator,
+    /// salt and initialization vector are regenerated
+    pub fn encrypt_custom<R: Rng>(&mut self, data: &[u8], passphrase: &str, rng: &mut R) {
+        let mut buf_salt: [u8; KDF_SALT_BYTES] = [0; KDF_SALT_BYTES];
+        rng.fill_bytes(&mut buf_salt);
+        self.kdf_params.salt = Salt::from(buf_salt);
+
+        let derived =
+            self.kdf_params
+                .kdf
+                .derive(self.kdf_params.dklen, &self.kdf_params.salt, passphrase);
+
+        let mut buf_iv: [u8; CIPHER_IV_BYTES] = [0; CIPHER_IV_BYTES];
+        rng.fill_bytes(&mut buf_iv);
+        self.cipher_params.iv = Iv::from(buf_iv);
+
+        self.cipher_text = self
+            .cipher
+            .encrypt(data, &derived[0..16], &self.cipher_params.iv);
+
+        let mut v = derived[16..32].to_vec();
+        v.extend_from_slice(&self.cipher_text);
+        self.mac = Mac::from(keccak256(&v));
+    }
+}
+
 impl Default for KeyFile {
     fn default() -> Self {
         KeyFile {
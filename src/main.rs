@@ -4,6 +4,7 @@
 #[cfg(feature = "nightly")]
 extern crate test;
 
+mod chains;
 mod contract;
 mod core;
 mod keystore;
@@ -20,6 +21,37 @@ mod tests {
     pub use super::*;
     pub use hex::{FromHex, ToHex};
     pub use regex::Regex;
+
+    use crate::chains::ChainRegistry;
+    use crate::keystore::{Kdf, KeyFile};
+    use crate::storage::{StorageController, StorageType};
+
+    /// Address shared by tests
+    pub const TEST_ADDRESS: &str = "0xc0de379b51d582e1600c76dd1efee8ed024b844a";
+
+    /// Parsed `TEST_ADDRESS`
+    pub fn test_address() -> Address {
+        TEST_ADDRESS.parse().unwrap()
+    }
+
+    /// `KeyFile` of a random key, encrypted with `1234567890` passphrase
+    /// and cheap KDF params to keep tests fast
+    pub fn keyfile() -> KeyFile {
+        KeyFile::new_custom(
+            PrivateKey::gen(),
+            "1234567890",
+            Kdf::from((8, 2, 1)),
+            &mut rand::thread_rng(),
+            Some("name".to_string()),
+            None,
+        )
+        .unwrap()
+    }
+
+    /// Controller over in-memory storages of built-in chains
+    pub fn storage_ctrl() -> StorageController {
+        StorageController::new("", StorageType::InMemory, ChainRegistry::default()).unwrap()
+    }
 }
 
 const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
@@ -3,17 +3,23 @@
 //!
 
 use super::error::Error;
-use crate::core::{PrivateKey, PRIVATE_KEY_BYTES};
+use crate::core::{Address, PrivateKey, PRIVATE_KEY_BYTES};
 use crate::util::to_bytes;
 use bitcoin::network::constants::Network;
 use bitcoin::util::bip32::ChildNumber;
-use bitcoin::util::bip32::ExtendedPrivKey;
+use bitcoin::util::bip32::{ExtendedPrivKey, ExtendedPubKey};
 use regex::Regex;
 use secp256k1::Secp256k1;
-use std::ops;
+use std::{fmt, ops};
 
 const DERIVATION_INDEX_SIZE: usize = 4;
 
+/// BIP44 purpose field
+const BIP44_PURPOSE: u32 = 44;
+
+/// SLIP-44 coin type shared by all testnets
+const TESTNET_COIN_TYPE: u32 = 1;
+
 lazy_static::lazy_static! {
     static ref HD_PATH_RE: Regex = Regex::new(r#"^m/{1}[^0-9'/]*"#).unwrap();
 }
@@ -63,6 +69,49 @@ impl HDPath {
 
         Ok(HDPath(res))
     }
+
+    /// Extend path with a normal (non-hardened) child index
+    ///
+    /// # Arguments:
+    ///
+    /// * index - child index
+    ///
+    pub fn child(&self, index: u32) -> Result<Self, Error> {
+        let mut res = self.0.clone();
+        res.push(ChildNumber::from_normal_idx(index)?);
+
+        Ok(HDPath(res))
+    }
+
+    /// Create path with BIP44 account level (third one) replaced
+    ///
+    /// # Arguments:
+    ///
+    /// * account - account index (hardened)
+    ///
+    pub fn with_account(&self, account: u32) -> Result<Self, Error> {
+        if self.0.len() < 3 {
+            return Err(format!("HD path without account level: {}", self).into());
+        }
+
+        let mut res = self.0.clone();
+        res[2] = ChildNumber::from_hardened_idx(account)?;
+
+        Ok(HDPath(res))
+    }
+}
+
+impl fmt::Display for HDPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for c in &self.0 {
+            match *c {
+                ChildNumber::Normal { index } => write!(f, "/{}", index)?,
+                ChildNumber::Hardened { index } => write!(f, "/{}'", index)?,
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ops::Deref for HDPath {
@@ -79,6 +128,40 @@ impl AsRef<[ChildNumber]> for HDPath {
     }
 }
 
+/// Get registered [SLIP-44](https://github.com/satoshilabs/slips/blob/master/slip-0044.md)
+/// coin type for a chain
+///
+/// # Arguments:
+///
+/// * chain_id - target chain id
+///
+pub fn coin_type(chain_id: u64) -> u32 {
+    match chain_id {
+        1 => 60,
+        61 => 61,
+        30 => 137,
+        31 => 37310,
+        _ => TESTNET_COIN_TYPE,
+    }
+}
+
+/// Build default BIP44 base path (without address index) for a chain in format
+/// `m/44'/<coin_type>'/<account>'/0`
+///
+/// # Arguments:
+///
+/// * chain_id - target chain id
+/// * account - account index (hardened)
+///
+pub fn default_base_path(chain_id: u64, account: u32) -> Result<HDPath, Error> {
+    Ok(HDPath(vec![
+        ChildNumber::from_hardened_idx(BIP44_PURPOSE)?,
+        ChildNumber::from_hardened_idx(coin_type(chain_id))?,
+        ChildNumber::from_hardened_idx(account)?,
+        ChildNumber::from_normal_idx(0)?,
+    ]))
+}
+
 /// Generate `PrivateKey` using BIP32
 ///
 ///  # Arguments:
@@ -95,6 +178,96 @@ pub fn generate_key(path: &HDPath, seed: &[u8]) -> Result<PrivateKey, Error> {
     Ok(key)
 }
 
+/// Generate a range of `PrivateKey`s for children of a base path
+/// Master node and base path are derived only once
+///
+///  # Arguments:
+///
+///  * base - base derivation path, child index is appended to it
+///  * seed - seed data for master node
+///  * start - index of the first child
+///  * count - number of children
+///
+pub fn generate_keys(
+    base: &HDPath,
+    seed: &[u8],
+    start: u32,
+    count: u32,
+) -> Result<Vec<(HDPath, PrivateKey)>, Error> {
+    let secp = Secp256k1::signing_only();
+    let parent = ExtendedPrivKey::new_master(Network::Bitcoin, seed)
+        .and_then(|k| k.derive_priv(&secp, base))?;
+
+    let end = start
+        .checked_add(count)
+        .ok_or_else(|| Error::from("HD path index range overflow"))?;
+
+    let mut keys = Vec::with_capacity(count as usize);
+    for index in start..end {
+        let child = ChildNumber::from_normal_idx(index)?;
+        let sk = parent.ckd_priv(&secp, child)?;
+        let key = PrivateKey::try_from(&sk.private_key.to_bytes()[0..PRIVATE_KEY_BYTES])?;
+        keys.push((base.child(index)?, key));
+    }
+
+    Ok(keys)
+}
+
+/// Generate extended public key (`xpub`) for a HD path
+///
+///  # Arguments:
+///
+///  * path - key derivation path
+///  * seed - seed data for master node
+///
+pub fn generate_xpub(path: &HDPath, seed: &[u8]) -> Result<ExtendedPubKey, Error> {
+    let secp = Secp256k1::new();
+    let sk = ExtendedPrivKey::new_master(Network::Bitcoin, seed)
+        .and_then(|k| k.derive_priv(&secp, path))?;
+
+    Ok(ExtendedPubKey::from_private(&secp, &sk))
+}
+
+/// Parse extended public key from its base58 representation
+///
+///  # Arguments:
+///
+///  * xpub - base58 encoded extended public key
+///
+pub fn parse_xpub(xpub: &str) -> Result<ExtendedPubKey, Error> {
+    xpub.trim()
+        .parse::<ExtendedPubKey>()
+        .map_err(|e| Error::KeyGenerationError(format!("Invalid xpub: {}", e)))
+}
+
+/// Derive a range of child `Address`es from an extended public key
+/// Only normal (non-hardened) children can be derived without private key
+///
+///  # Arguments:
+///
+///  * xpub - parent extended public key
+///  * start - index of the first child
+///  * count - number of children
+///
+pub fn derive_xpub_addresses(
+    xpub: &ExtendedPubKey,
+    start: u32,
+    count: u32,
+) -> Result<Vec<(u32, Address)>, Error> {
+    let secp = Secp256k1::verification_only();
+    let end = start
+        .checked_add(count)
+        .ok_or_else(|| Error::from("HD path index range overflow"))?;
+
+    let mut addresses = Vec::with_capacity(count as usize);
+    for index in start..end {
+        let child = xpub.ckd_pub(&secp, ChildNumber::from_normal_idx(index)?)?;
+        addresses.push((index, Address::from_public_key(&child.public_key.key)));
+    }
+
+    Ok(addresses)
+}
+
 /// Parse HD path into byte array
 ///
 /// # Arguments:
@@ -151,7 +324,6 @@ pub fn to_prefixed_path(hd_str: &str) -> Result<Vec<u8>, failure::Error> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::core::Address;
     use hex::FromHex;
     use std::str::FromStr;
 
@@ -168,6 +340,104 @@ mod test {
         Ok(assert_eq!(parsed, exp))
     }
 
+    #[test]
+    fn should_build_default_path() {
+        assert_eq!(
+            default_base_path(61, 0)
+                .unwrap()
+                .child(5)
+                .unwrap()
+                .to_string(),
+            "m/44'/61'/0'/0/5"
+        );
+        assert_eq!(
+            default_base_path(1, 2)
+                .unwrap()
+                .child(0)
+                .unwrap()
+                .to_string(),
+            "m/44'/60'/2'/0/0"
+        );
+        assert_eq!(
+            default_base_path(30, 0).unwrap().to_string(),
+            "m/44'/137'/0'/0"
+        );
+        assert_eq!(
+            default_base_path(62, 0).unwrap().to_string(),
+            "m/44'/1'/0'/0"
+        );
+    }
+
+    #[test]
+    fn should_replace_account() {
+        let path = HDPath::try_from("m/44'/61'/0'/0").unwrap();
+
+        assert_eq!(path.with_account(3).unwrap().to_string(), "m/44'/61'/3'/0");
+        assert!(HDPath::try_from("m/44'/61'")
+            .unwrap()
+            .with_account(3)
+            .is_err());
+    }
+
+    #[test]
+    fn display_hdpath() {
+        let path = "m/44'/61'/0'/0/12";
+        assert_eq!(HDPath::try_from(path).unwrap().to_string(), path);
+    }
+
+    #[test]
+    fn test_xpub_addresses_derivation() -> Result<(), Error> {
+        let seed = Vec::from_hex(
+            "b15509eaa2d09d3efd3e006ef42151b3\
+             0367dc6e3aa5e44caba3fe4d3e352e65\
+             101fbdb86a96776b91946ff06f8eac59\
+             4dc6ee1d3e82a42dfe1b40fef6bcc3fd",
+        )
+        .unwrap();
+
+        let base = HDPath::try_from("m/44'/60'/160720'/0'")?;
+        let xpub = generate_xpub(&base, &seed)?;
+        let xpub = parse_xpub(&xpub.to_string())?;
+
+        let addresses = derive_xpub_addresses(&xpub, 0, 2)?;
+        let keys = generate_keys(&base, &seed, 0, 2)?;
+        assert_eq!(addresses[0].1, keys[0].1.to_address().unwrap());
+        assert_eq!(addresses[1].1, keys[1].1.to_address().unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_fail_parse_xpub() {
+        assert!(parse_xpub("xpub123").is_err());
+    }
+
+    #[test]
+    fn test_keys_range_generation() -> Result<(), Error> {
+        let seed = Vec::from_hex(
+            "b15509eaa2d09d3efd3e006ef42151b3\
+             0367dc6e3aa5e44caba3fe4d3e352e65\
+             101fbdb86a96776b91946ff06f8eac59\
+             4dc6ee1d3e82a42dfe1b40fef6bcc3fd",
+        )
+        .unwrap();
+
+        let base = HDPath::try_from("m/44'/60'/160720'/0'")?;
+        let keys = generate_keys(&base, &seed, 0, 3)?;
+
+        assert_eq!(keys.len(), 3);
+        for (i, (path, key)) in keys.iter().enumerate() {
+            assert_eq!(path, &base.child(i as u32)?);
+            assert_eq!(*key, generate_key(path, &seed)?);
+        }
+        assert_eq!(
+            Address::from_str("0x79B9E1af57Ebb2600a134e28eA05e52A312957A6").unwrap(),
+            keys[0].1.to_address().unwrap()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_key_generation() -> Result<(), Error> {
         let seed = Vec::from_hex(
@@ -4,8 +4,11 @@
 use serde::{Deserialize, Serialize};
 
 use super::Error;
-use crate::util;
+use crate::chains::ChainRegistry;
+use crate::contract::{DecodedCall, DecodedEvent};
+use crate::storage::{AccountInfo, TrashEntry};
 use serde_json::Value;
+use uuid::Uuid;
 
 /// Trait to access a common chain name and id params
 ///
@@ -16,37 +19,42 @@ pub trait CommonChainParams {
 
 /// Check correspondence between chain name and chain numerical ID
 /// If succeed, returns tuple of chain name and chain id.
+/// Chain name is resolved to its canonical form (aliases are replaced).
 ///
 ///
 /// # Arguments
 ///
 /// * p - trait object to access chain name and id
+/// * chains - registry of supported chains
 ///
 /// # Errors
 ///
 /// Return `Error` if parameters does not match
 ///
-pub fn extract_chain_params(p: &dyn CommonChainParams) -> Result<(String, u8), Error> {
+pub fn extract_chain_params(
+    p: &dyn CommonChainParams,
+    chains: &ChainRegistry,
+) -> Result<(String, u64), Error> {
     let name_param = p.get_chain();
-    let id_param = p.get_chain_id();
-    let id: u8;
+    let id_param = p.get_chain_id().map(|id| id as u64);
+    let id: u64;
     let name: String;
 
     if !name_param.is_empty() && id_param.is_some() {
-        id = check_chain_name(&name_param)?;
-        name = check_chain_id(id_param.unwrap() as u8)?;
-        if (id_param.unwrap() as u8) != id {
+        id = check_chain_name(&name_param, chains)?;
+        name = check_chain_id(id_param.unwrap(), chains)?;
+        if id_param.unwrap() != id {
             return Err(Error::InvalidDataFormat(format!(
                 "Inconsistent chain name: {} and chain id: {}",
                 name_param, id
             )));
         }
     } else if !name_param.is_empty() {
-        name = name_param.clone();
-        id = check_chain_name(&name_param)?;
+        id = check_chain_name(&name_param, chains)?;
+        name = check_chain_id(id, chains)?;
     } else if id_param.is_some() {
-        id = id_param.unwrap() as u8;
-        name = check_chain_id(id)?;
+        id = id_param.unwrap();
+        name = check_chain_id(id, chains)?;
     } else {
         return Err(Error::InvalidDataFormat(
             "Required chain name or chain id parameter".to_string(),
@@ -56,9 +64,9 @@ pub fn extract_chain_params(p: &dyn CommonChainParams) -> Result<(String, u8), E
     Ok((name, id))
 }
 
-fn check_chain_name(n: &str) -> Result<u8, Error> {
-    match util::to_chain_id(n) {
-        Some(id) => Ok(id),
+fn check_chain_name(n: &str, chains: &ChainRegistry) -> Result<u64, Error> {
+    match chains.find(n) {
+        Some(chain) => Ok(chain.chain_id),
         None => Err(Error::InvalidDataFormat(format!(
             "Invalid chain name: {}",
             n
@@ -66,9 +74,9 @@ fn check_chain_name(n: &str) -> Result<u8, Error> {
     }
 }
 
-fn check_chain_id(id: u8) -> Result<String, Error> {
-    match util::to_chain_name(id) {
-        Some(n) => Ok(n.to_string()),
+fn check_chain_id(id: u64, chains: &ChainRegistry) -> Result<String, Error> {
+    match chains.find_by_id(id) {
+        Some(chain) => Ok(chain.name.clone()),
         None => Err(Error::InvalidDataFormat(format!(
             "Invalid chain id: {}",
             id
@@ -128,6 +136,54 @@ impl<U: Default> SignParams<(String, String, String, U)> {
     }
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum FunctionCallParams<U> {
+    Left(Value, FunctionParams),
+    Right(U),
+}
+
+impl<U: Default> FunctionCallParams<(Value, FunctionParams, U)> {
+    pub fn into_full(self) -> (Value, FunctionParams, U) {
+        match self {
+            FunctionCallParams::Left(c, f) => (c, f, U::default()),
+            FunctionCallParams::Right((c, f, u)) => (c, f, u),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum DecodeCallParams<U> {
+    Left(String, Value),
+    Right(U),
+}
+
+impl<U: Default> DecodeCallParams<(String, Value, U)> {
+    pub fn into_full(self) -> (String, Value, U) {
+        match self {
+            DecodeCallParams::Left(d, c) => (d, c, U::default()),
+            DecodeCallParams::Right((d, c, u)) => (d, c, u),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum DecodeResultParams<U> {
+    Left(String, Value, String),
+    Right(U),
+}
+
+impl<U: Default> DecodeResultParams<(String, Value, String, U)> {
+    pub fn into_full(self) -> (String, Value, String, U) {
+        match self {
+            DecodeResultParams::Left(d, c, f) => (d, c, f, U::default()),
+            DecodeResultParams::Right((d, c, f, u)) => (d, c, f, u),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
 pub enum SignTypedDataParams<U> {
@@ -211,6 +267,46 @@ pub struct ListAccountAccount {
     pub description: String,
     pub hardware: bool,
     pub is_hidden: bool,
+    #[serde(default)]
+    pub watch_only: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_from: Option<String>,
+}
+
+impl<'a> From<&'a AccountInfo> for ListAccountAccount {
+    fn from(info: &'a AccountInfo) -> Self {
+        ListAccountAccount {
+            name: info.name.clone(),
+            address: info.address.clone(),
+            description: info.description.clone(),
+            hardware: info.is_hardware,
+            is_hidden: info.is_hidden,
+            watch_only: info.is_watch_only,
+            tags: info.tags.clone(),
+            shared_from: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DeletedAccount {
+    #[serde(flatten)]
+    pub account: ListAccountAccount,
+    pub deleted_at: i64,
+}
+
+impl<'a> From<&'a TrashEntry> for DeletedAccount {
+    fn from(entry: &'a TrashEntry) -> Self {
+        let mut info = AccountInfo::from(entry.keyfile.clone());
+        info.filename = entry.filename.clone();
+
+        DeletedAccount {
+            account: ListAccountAccount::from(&info),
+            deleted_at: entry.deleted_at,
+        }
+    }
 }
 
 #[derive(Deserialize, Default, Debug)]
@@ -222,6 +318,8 @@ pub struct ListAccountsAdditional {
     #[serde(default)]
     pub show_hidden: bool,
     #[serde(default)]
+    pub checksum: bool,
+    #[serde(default)]
     pub hd_path: Option<String>,
 }
 
@@ -240,12 +338,95 @@ pub struct SelectedAccount {
     pub address: String,
 }
 
+#[derive(Deserialize)]
+pub struct DeleteAccountAccount {
+    pub address: String,
+    pub passphrase: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DecodedFunctionCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    #[serde(flatten)]
+    pub call: DecodedCall,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LogParams {
+    #[serde(default)]
+    pub address: Option<String>,
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub data: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DecodedLog {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    #[serde(flatten)]
+    pub event: DecodedEvent,
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct DecodeLogsAdditional {
+    #[serde(default)]
+    pub chain: String,
+    #[serde(default)]
+    pub chain_id: Option<usize>,
+    #[serde(default)]
+    pub contract: Option<Value>,
+}
+
+impl CommonChainParams for DecodeLogsAdditional {
+    fn get_chain(&self) -> String {
+        self.chain.clone()
+    }
+
+    fn get_chain_id(&self) -> Option<usize> {
+        self.chain_id
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ContractQuery {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum UpdateContractParams<U> {
+    Left(ContractQuery, Value),
+    Right(U),
+}
+
+impl<U: Default> UpdateContractParams<(ContractQuery, Value, U)> {
+    pub fn into_full(self) -> (ContractQuery, Value, U) {
+        match self {
+            UpdateContractParams::Left(q, c) => (q, c, U::default()),
+            UpdateContractParams::Right((q, c, u)) => (q, c, u),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LinkAccountAccount {
+    pub address: String,
+    pub chains: Vec<String>,
+}
+
 #[derive(Deserialize, Default, Debug)]
 pub struct CommonAdditional {
     #[serde(default)]
     pub chain: String,
     #[serde(default)]
     pub chain_id: Option<usize>,
+    #[serde(default)]
+    pub caller: Option<String>,
 }
 
 impl CommonChainParams for CommonAdditional {
@@ -258,10 +439,11 @@ impl CommonChainParams for CommonAdditional {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SignTxTransaction {
     pub from: String,
-    pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
     pub gas: String,
     #[serde(rename = "gasPrice")]
     pub gas_price: String,
@@ -272,6 +454,20 @@ pub struct SignTxTransaction {
     pub nonce: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct DeployTransaction {
+    #[serde(flatten)]
+    pub transaction: SignTxTransaction,
+    #[serde(default, rename = "constructorArgs")]
+    pub constructor_args: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Deployment {
+    pub signed: String,
+    pub address: String,
+}
+
 #[derive(Deserialize, Default, Debug)]
 pub struct SignTxAdditional {
     #[serde(default)]
@@ -280,6 +476,8 @@ pub struct SignTxAdditional {
     pub chain_id: Option<usize>,
     #[serde(default)]
     pub hd_path: Option<String>,
+    #[serde(default)]
+    pub caller: Option<String>,
 }
 
 impl CommonChainParams for SignTxAdditional {
@@ -294,7 +492,10 @@ impl CommonChainParams for SignTxAdditional {
 
 #[derive(Deserialize, Default, Debug)]
 pub struct FunctionParams {
-    pub values: Vec<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub values: Vec<Value>,
+    #[serde(default)]
     pub types: Vec<String>,
 }
 
@@ -306,9 +507,183 @@ pub struct NewMnemonicAccount {
     pub description: String,
     pub passphrase: String,
     pub mnemonic: String,
+    #[serde(default)]
+    pub hd_path: Option<String>,
+    #[serde(default)]
+    pub account: Option<u32>,
+    #[serde(default)]
+    pub index: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MnemonicRangeAccounts {
+    #[serde(default)]
+    pub name_template: String,
+    #[serde(default)]
+    pub description: String,
+    pub passphrase: String,
+    pub mnemonic: String,
+    #[serde(default)]
+    pub base_path: Option<String>,
+    #[serde(default)]
+    pub start: u32,
+    pub count: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DerivedAddress {
+    pub address: String,
     pub hd_path: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct WatchOnlyAccountParams {
+    pub address: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TokenTransferParams {
+    pub token: String,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    pub gas: String,
+    #[serde(rename = "gasPrice")]
+    pub gas_price: String,
+    pub nonce: String,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NftTransferParams {
+    pub token: String,
+    pub from: String,
+    pub to: String,
+    #[serde(rename = "tokenId")]
+    pub token_id: String,
+    pub gas: String,
+    #[serde(rename = "gasPrice")]
+    pub gas_price: String,
+    pub nonce: String,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TokenTransfer {
+    pub transaction: SignTxTransaction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signed: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Create2Params {
+    pub deployer: String,
+    pub salt: String,
+    #[serde(default, rename = "initCode")]
+    pub init_code: Option<String>,
+    #[serde(default, rename = "initCodeHash")]
+    pub init_code_hash: Option<String>,
+    #[serde(default, rename = "encodeDeploy")]
+    pub encode_deploy: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Create2Address {
+    pub address: String,
+    #[serde(rename = "initCodeHash")]
+    pub init_code_hash: String,
+    #[serde(rename = "deployCall", skip_serializing_if = "Option::is_none")]
+    pub deploy_call: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FeeBounds {
+    pub min: String,
+    pub max: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TransactionPreview {
+    pub chain: String,
+    pub chain_id: u64,
+    pub from: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_label: Option<String>,
+    pub to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_label: Option<String>,
+    pub value: String,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub gas_price: String,
+    pub fee: FeeBounds,
+    pub data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call: Option<DecodedCall>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UnsignedTransaction {
+    pub raw: String,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImportSeedAccount {
+    pub mnemonic: String,
+    pub passphrase: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SeedInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SelectedSeed {
+    pub seed_id: Uuid,
+    pub passphrase: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImportXpubAccount {
+    pub xpub: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct XpubAddressesParams {
+    pub xpub: String,
+    #[serde(default)]
+    pub start: u32,
+    pub count: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct XpubAddress {
+    pub address: String,
+    pub index: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -318,9 +693,10 @@ mod tests {
         let params = CommonAdditional {
             chain: "etc".to_string(),
             chain_id: Some(61),
+            ..Default::default()
         };
 
-        let (name, id) = extract_chain_params(&params).unwrap();
+        let (name, id) = extract_chain_params(&params, &ChainRegistry::default()).unwrap();
         assert_eq!(name, "etc");
         assert_eq!(id, 61);
     }
@@ -330,9 +706,10 @@ mod tests {
         let params = CommonAdditional {
             chain: "".to_string(),
             chain_id: Some(61),
+            ..Default::default()
         };
 
-        let (name, id) = extract_chain_params(&params).unwrap();
+        let (name, id) = extract_chain_params(&params, &ChainRegistry::default()).unwrap();
         assert_eq!(name, "etc");
         assert_eq!(id, 61);
     }
@@ -342,9 +719,10 @@ mod tests {
         let params = CommonAdditional {
             chain: "etc".to_string(),
             chain_id: None,
+            ..Default::default()
         };
 
-        let (name, id) = extract_chain_params(&params).unwrap();
+        let (name, id) = extract_chain_params(&params, &ChainRegistry::default()).unwrap();
         assert_eq!(name, "etc");
         assert_eq!(id, 61);
     }
@@ -354,9 +732,23 @@ mod tests {
         let params = CommonAdditional {
             chain: "".to_string(),
             chain_id: None,
+            ..Default::default()
         };
 
-        let res = extract_chain_params(&params);
+        let res = extract_chain_params(&params, &ChainRegistry::default());
         assert!(res.is_err());
     }
+
+    #[test]
+    fn should_resolve_chain_alias() {
+        let params = CommonAdditional {
+            chain: "mainnet".to_string(),
+            chain_id: None,
+            ..Default::default()
+        };
+
+        let (name, id) = extract_chain_params(&params, &ChainRegistry::default()).unwrap();
+        assert_eq!(name, "etc");
+        assert_eq!(id, 61);
+    }
 }
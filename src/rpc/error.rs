@@ -28,6 +28,8 @@ pub enum Error {
     MnemonicError(String),
     /// Typed Data Error
     TypedDataError(String),
+    /// Signing or storing private key requested for watch-only account
+    WatchOnlyAccount(String),
 }
 
 impl From<keystore::Error> for Error {
@@ -106,6 +108,9 @@ impl fmt::Display for Error {
             Error::ContractAbiError(ref str) => write!(f, "Contract ABI error: {}", str),
             Error::MnemonicError(ref str) => write!(f, "Mnemonic error: {}", str),
             Error::TypedDataError(ref str) => write!(f, "Typed data error: {}", str),
+            Error::WatchOnlyAccount(ref str) => {
+                write!(f, "Watch-only account has no private key: {}", str)
+            }
         }
     }
 }
@@ -10,14 +10,24 @@ use super::core;
 use super::keystore::KdfDepthLevel;
 use super::storage::{self, StorageController};
 use super::util::{align_bytes, to_arr, to_even_str, to_u64, trim_hex, ToHex};
-use jsonrpc_core::{Error as JsonRpcError, IoHandler, Params};
+use jsonrpc_core::{Error as JsonRpcError, MetaIoHandler, Metadata, Params};
+use jsonrpc_http_server::hyper::server::Request;
 use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
 use log::Level;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::{self, Value};
 use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+
+/// Request metadata taken from the transport, not from request params
+#[derive(Debug, Clone, Default)]
+pub struct Meta {
+    /// Network address of peer that sent the request
+    pub peer: Option<SocketAddr>,
+}
+
+impl Metadata for Meta {}
 
 fn wrapper<T: Serialize>(value: Result<T, Error>) -> Result<Value, JsonRpcError> {
     if value.is_err() {
@@ -48,12 +58,60 @@ where
 /// * addr - socket address
 /// * storage_ctrl - controller for `Keyfile` storage
 /// * sec_level - security level
+/// * threads - number of threads processing requests
+/// * admin_addr - socket address for admin methods, they aren't served if not set
 ///
-pub fn start(addr: &SocketAddr, storage_ctrl: StorageController, sec_level: Option<KdfDepthLevel>) {
-    let sec_level = sec_level.unwrap_or_default();
-    let storage_ctrl = Arc::new(Mutex::new(storage_ctrl));
+pub fn start(
+    addr: &SocketAddr,
+    storage_ctrl: StorageController,
+    sec_level: Option<KdfDepthLevel>,
+    threads: usize,
+    admin_addr: Option<SocketAddr>,
+) {
+    let storage_ctrl = Arc::new(storage_ctrl);
+    let io = build_handler(Arc::clone(&storage_ctrl), sec_level.unwrap_or_default());
+
+    let server = ServerBuilder::new(io)
+        .cors(DomainsValidation::AllowOnly(vec![
+            AccessControlAllowOrigin::Any,
+            AccessControlAllowOrigin::Null,
+        ]))
+        .meta_extractor(|req: &Request| Meta {
+            peer: req.remote_addr(),
+        })
+        .threads(threads)
+        .start_http(addr)
+        .expect("Expect to build HTTP RPC server");
+
+    if log::log_enabled!(Level::Info) {
+        log::info!("Connector started on http://{}", server.address());
+    }
+
+    // No CORS headers are sent, so browser pages can't read responses
+    let _admin = admin_addr.map(|admin_addr| {
+        let admin = ServerBuilder::new(build_admin_handler(storage_ctrl))
+            .allow_only_bind_host()
+            .start_http(&admin_addr)
+            .expect("Expect to build HTTP admin RPC server");
+        log::info!("Admin connector started on http://{}", admin.address());
+        admin
+    });
+
+    server.wait();
+}
 
-    let mut io = IoHandler::default();
+/// Build handler with all JSON-RPC methods registered
+///
+/// # Arguments
+///
+/// * storage_ctrl - controller for `Keyfile` storage
+/// * sec_level - security level
+///
+fn build_handler(
+    storage_ctrl: Arc<StorageController>,
+    sec_level: KdfDepthLevel,
+) -> MetaIoHandler<Meta> {
+    let mut io = MetaIoHandler::default();
 
     {
         io.add_method("openrpc_discover", move |_: Params| {
@@ -98,6 +156,48 @@ pub fn start(addr: &SocketAddr, storage_ctrl: StorageController, sec_level: Opti
         });
     }
 
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_deleteAccount", move |p: Params| {
+            wrapper(serves::delete_account(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_listDeletedAccounts", move |p: Params| {
+            wrapper(serves::list_deleted_accounts(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_restoreAccount", move |p: Params| {
+            wrapper(serves::restore_account(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_purgeAccount", move |p: Params| {
+            wrapper(serves::purge_account(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_linkAccount", move |p: Params| {
+            wrapper(serves::link_account(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_unlinkAccount", move |p: Params| {
+            wrapper(serves::unlink_account(parse(p)?, &storage_ctrl))
+        });
+    }
+
     {
         let storage_ctrl = Arc::clone(&storage_ctrl);
         io.add_method("signer_importAccount", move |p: Params| {
@@ -121,28 +221,102 @@ pub fn start(addr: &SocketAddr, storage_ctrl: StorageController, sec_level: Opti
 
     {
         let storage_ctrl = Arc::clone(&storage_ctrl);
-        io.add_method("signer_signTransaction", move |p: Params| {
-            wrapper(serves::sign_transaction(parse(p)?, &storage_ctrl))
+        io.add_method_with_meta("signer_signTransaction", move |p: Params, meta: Meta| {
+            wrapper(serves::sign_transaction(parse(p)?, &storage_ctrl, &meta))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method_with_meta("signer_sign", move |p: Params, meta: Meta| {
+            wrapper(serves::sign(parse(p)?, &storage_ctrl, &meta))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method_with_meta("signer_signTypedData", move |p: Params, meta: Meta| {
+            wrapper(serves::sign_typed_data(parse(p)?, &storage_ctrl, &meta))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_importWatchOnly", move |p: Params| {
+            wrapper(serves::import_watch_only(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method_with_meta("signer_signDeployment", move |p: Params, meta: Meta| {
+            wrapper(serves::sign_deployment(parse(p)?, &storage_ctrl, &meta))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_buildTransaction", move |p: Params| {
+            wrapper(serves::build_transaction(parse(p)?, &storage_ctrl))
         });
     }
 
     {
         let storage_ctrl = Arc::clone(&storage_ctrl);
-        io.add_method("signer_sign", move |p: Params| {
-            wrapper(serves::sign(parse(p)?, &storage_ctrl))
+        io.add_method("signer_previewTransaction", move |p: Params| {
+            wrapper(serves::preview_transaction(parse(p)?, &storage_ctrl))
         });
     }
 
     {
         let storage_ctrl = Arc::clone(&storage_ctrl);
-        io.add_method("signer_signTypedData", move |p: Params| {
-            wrapper(serves::sign_typed_data(parse(p)?, &storage_ctrl))
+        io.add_method_with_meta("signer_buildTokenTransfer", move |p: Params, meta: Meta| {
+            wrapper(serves::build_token_transfer(
+                parse(p)?,
+                &storage_ctrl,
+                &meta,
+            ))
         });
     }
 
     {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method_with_meta("signer_buildNftTransfer", move |p: Params, meta: Meta| {
+            wrapper(serves::build_nft_transfer(parse(p)?, &storage_ctrl, &meta))
+        });
+    }
+
+    {
+        io.add_method("signer_computeCreate2Address", move |p: Params| {
+            wrapper(serves::compute_create2_address(parse(p)?))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
         io.add_method("signer_encodeFunctionCall", move |p: Params| {
-            wrapper(serves::encode_function_call(parse(p)?))
+            wrapper(serves::encode_function_call(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_decodeFunctionCall", move |p: Params| {
+            wrapper(serves::decode_function_call(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_decodeFunctionResult", move |p: Params| {
+            wrapper(serves::decode_function_result(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_decodeLogs", move |p: Params| {
+            wrapper(serves::decode_logs(parse(p)?, &storage_ctrl))
         });
     }
 
@@ -160,12 +334,26 @@ pub fn start(addr: &SocketAddr, storage_ctrl: StorageController, sec_level: Opti
         });
     }
 
-    //    {
-    //        let storage_ctrl = Arc::clone(&storage_ctrl);
-    //        io.add_method("signer_exportContract", move |p: Params| {
-    //            wrapper(serves::export_contract(parse(p)?, &storage_ctrl))
-    //        });
-    //    }
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_exportContract", move |p: Params| {
+            wrapper(serves::export_contract(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_updateContract", move |p: Params| {
+            wrapper(serves::update_contract(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_deleteContract", move |p: Params| {
+            wrapper(serves::delete_contract(parse(p)?, &storage_ctrl))
+        });
+    }
 
     {
         io.add_method("signer_generateMnemonic", move |_: Params| {
@@ -184,17 +372,734 @@ pub fn start(addr: &SocketAddr, storage_ctrl: StorageController, sec_level: Opti
         });
     }
 
-    let server = ServerBuilder::new(io)
-        .cors(DomainsValidation::AllowOnly(vec![
-            AccessControlAllowOrigin::Any,
-            AccessControlAllowOrigin::Null,
-        ]))
-        .start_http(addr)
-        .expect("Expect to build HTTP RPC server");
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_importMnemonicRange", move |p: Params| {
+            wrapper(serves::import_mnemonic_range(
+                parse(p)?,
+                &sec_level,
+                &storage_ctrl,
+            ))
+        });
+    }
 
-    if log::log_enabled!(Level::Info) {
-        log::info!("Connector started on http://{}", server.address());
+    {
+        io.add_method("signer_deriveAddresses", move |p: Params| {
+            wrapper(serves::derive_addresses(parse(p)?))
+        });
     }
 
-    server.wait();
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_importSeed", move |p: Params| {
+            wrapper(serves::import_seed(parse(p)?, &sec_level, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_listSeeds", move |p: Params| {
+            wrapper(serves::list_seeds(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_exportXpub", move |p: Params| {
+            wrapper(serves::export_xpub(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_importXpub", move |p: Params| {
+            wrapper(serves::import_xpub(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_listXpubs", move |p: Params| {
+            wrapper(serves::list_xpubs(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_listXpubAddresses", move |p: Params| {
+            wrapper(serves::list_xpub_addresses(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_listChains", move |_: Params| {
+            wrapper(serves::list_chains(&storage_ctrl))
+        });
+    }
+
+    io
+}
+
+/// Build handler with admin JSON-RPC methods, served on a separate listener
+///
+/// # Arguments
+///
+/// * storage_ctrl - controller for `Keyfile` storage
+///
+fn build_admin_handler(storage_ctrl: Arc<StorageController>) -> MetaIoHandler<Meta> {
+    let mut io = MetaIoHandler::default();
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_listAuditLog", move |p: Params| {
+            wrapper(serves::list_audit_log(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    {
+        let storage_ctrl = Arc::clone(&storage_ctrl);
+        io.add_method("signer_verifyAuditLog", move |p: Params| {
+            wrapper(serves::verify_audit_log(parse(p)?, &storage_ctrl))
+        });
+    }
+
+    io
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::storage_ctrl;
+    use serde_json::json;
+
+    const ABI: &str = r#"[{"constant":false,"inputs":[{"name":"to","type":"address"},
+        {"name":"value","type":"uint256"}],"name":"transfer","outputs":[{"name":"","type":"bool"}],
+        "payable":false,"type":"function"}]"#;
+
+    const TRANSFER: &str = "0xa9059cbb\
+                            0000000000000000000000000000000000000000000000000000000000000001\
+                            000000000000000000000000000000000000000000000000000000000000000a";
+
+    fn handler() -> MetaIoHandler<Meta> {
+        build_handler(Arc::new(storage_ctrl()), KdfDepthLevel::default())
+    }
+
+    /// Call method, returning either result or error message
+    fn call(io: &MetaIoHandler<Meta>, method: &str, params: Value) -> Result<Value, String> {
+        call_with_meta(io, method, params, Meta::default())
+    }
+
+    /// Call method with metadata, returning either result or error message
+    fn call_with_meta(
+        io: &MetaIoHandler<Meta>,
+        method: &str,
+        params: Value,
+        meta: Meta,
+    ) -> Result<Value, String> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = io
+            .handle_request_sync(&request.to_string(), meta)
+            .expect("Expect response");
+        let response: Value = serde_json::from_str(&response).unwrap();
+
+        match response.get("result") {
+            Some(result) => Ok(result.clone()),
+            None => Err(response["error"]["message"].to_string()),
+        }
+    }
+
+    #[test]
+    fn should_encode_call_with_inline_abi_without_chain() {
+        let io = handler();
+        let abi: Value = serde_json::from_str(ABI).unwrap();
+        let values = json!(["0x0000000000000000000000000000000000000001", "10"]);
+
+        let res = call(
+            &io,
+            "signer_encodeFunctionCall",
+            json!([abi, { "name": "transfer", "values": values }]),
+        );
+        assert_eq!(res, Ok(json!(TRANSFER)));
+    }
+
+    #[test]
+    fn should_encode_call_by_signature_without_chain() {
+        let io = handler();
+
+        let res = call(
+            &io,
+            "signer_encodeFunctionCall",
+            json!([null, {
+                "name": "transfer",
+                "types": ["address", "uint256"],
+                "values": ["0x0000000000000000000000000000000000000001", "10"]
+            }]),
+        );
+        assert_eq!(res, Ok(json!(TRANSFER)));
+    }
+
+    #[test]
+    fn should_encode_params_with_prefix() {
+        let io = handler();
+
+        let res = call(
+            &io,
+            "signer_encodeFunctionCall",
+            json!([null, { "types": ["uint256"], "values": ["10"] }]),
+        );
+        assert_eq!(
+            res,
+            Ok(json!(
+                "0x000000000000000000000000000000000000000000000000000000000000000a"
+            ))
+        );
+    }
+
+    #[test]
+    fn should_require_chain_for_stored_contract() {
+        let io = handler();
+        let values = json!(["0x0000000000000000000000000000000000000001", "10"]);
+        let addr = "0xc0de379b51d582e1600c76dd1efee8ed024b844a";
+
+        let res = call(
+            &io,
+            "signer_encodeFunctionCall",
+            json!([addr, { "name": "transfer", "values": values }]),
+        );
+        assert!(res.unwrap_err().contains("chain"));
+
+        let abi: Value = serde_json::from_str(ABI).unwrap();
+        call(
+            &io,
+            "signer_importContract",
+            json!([{ "address": addr, "name": "Token", "abi": abi }, { "chain": "etc" }]),
+        )
+        .unwrap();
+        let res = call(
+            &io,
+            "signer_encodeFunctionCall",
+            json!([addr, { "name": "transfer", "values": values }, { "chain": "etc" }]),
+        );
+        assert_eq!(res, Ok(json!(TRANSFER)));
+    }
+
+    #[test]
+    fn should_decode_call_with_inline_abi_without_chain() {
+        let io = handler();
+        let abi: Value = serde_json::from_str(ABI).unwrap();
+
+        let res = call(&io, "signer_decodeFunctionCall", json!([TRANSFER, abi])).unwrap();
+        assert_eq!(res["name"], "transfer");
+        assert_eq!(res["params"][1]["value"], "10");
+
+        let res = call(
+            &io,
+            "signer_decodeFunctionResult",
+            json!([
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+                abi,
+                "transfer"
+            ]),
+        )
+        .unwrap();
+        assert_eq!(res["params"][0]["value"], true);
+    }
+
+    #[test]
+    fn should_reject_odd_length_call_data() {
+        let io = handler();
+        let abi: Value = serde_json::from_str(ABI).unwrap();
+
+        let res = call(
+            &io,
+            "signer_decodeFunctionCall",
+            json!([&TRANSFER[..TRANSFER.len() - 1], abi]),
+        );
+        assert!(res.unwrap_err().contains("Odd length"));
+    }
+
+    #[test]
+    fn should_sign_deployment_with_contract_address() {
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let kf = keyfile();
+        storage_ctrl.get_keystore("etc").unwrap().put(&kf).unwrap();
+
+        let deploy = json!({
+            "from": kf.address.to_string(),
+            "gas": "0x186a0",
+            "gasPrice": "0x3b9aca00",
+            "data": "0x6080604052",
+            "constructorArgs": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "nonce": "0x2a"
+        });
+        let res = call(
+            &io,
+            "signer_signDeployment",
+            json!([deploy, "1234567890", { "chain": "etc" }]),
+        )
+        .unwrap();
+        assert_eq!(res["address"], kf.address.contract_address(42).to_string());
+        assert!(res["signed"].as_str().unwrap().contains(
+            "60806040520000000000000000000000000000000000000000000000000000000000000001"
+        ));
+    }
+
+    #[test]
+    fn should_reject_deployment_with_odd_length_data() {
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let kf = keyfile();
+        storage_ctrl.get_keystore("etc").unwrap().put(&kf).unwrap();
+
+        let deploy = json!({
+            "from": kf.address.to_string(),
+            "gas": "0x186a0",
+            "gasPrice": "0x3b9aca00",
+            "data": "0x6080604052",
+            "constructorArgs": "0x001",
+            "nonce": "0x0"
+        });
+        let res = call(
+            &io,
+            "signer_signDeployment",
+            json!([deploy, "1234567890", { "chain": "etc" }]),
+        );
+        assert!(res.unwrap_err().contains("Odd length"));
+
+        let mut deploy = deploy;
+        deploy["data"] = json!("0x608060405");
+        deploy["constructorArgs"] = json!("0x01");
+        let res = call(
+            &io,
+            "signer_signDeployment",
+            json!([deploy, "1234567890", { "chain": "etc" }]),
+        );
+        assert!(res.unwrap_err().contains("Odd length"));
+    }
+
+    #[test]
+    fn should_export_xpub_from_stored_seed() {
+        let io = handler();
+        let mnemonic = "beyond stage sleep clip because twist token leaf atom beauty genius food \
+                        business side grid unable middle armed observe pair crouch tonight away coconut";
+        let path = "m/44'/60'/0'/0";
+
+        let id = call(
+            &io,
+            "signer_importSeed",
+            json!([{ "mnemonic": mnemonic, "passphrase": "1234567890", "name": "main" }, { "chain": "etc" }]),
+        )
+        .unwrap();
+        let res = call(&io, "signer_listSeeds", json!([{ "chain": "etc" }])).unwrap();
+        assert_eq!(res[0]["id"], id);
+        assert_eq!(res[0]["name"], "main");
+        assert!(res[0].get("crypto").is_none());
+
+        let expected = call(&io, "signer_exportXpub", json!([mnemonic, path])).unwrap();
+        let res = call(
+            &io,
+            "signer_exportXpub",
+            json!([{ "seed_id": id, "passphrase": "1234567890" }, path, { "chain": "etc" }]),
+        );
+        assert_eq!(res, Ok(expected));
+
+        let res = call(
+            &io,
+            "signer_exportXpub",
+            json!([{ "seed_id": id, "passphrase": "0987654321" }, path, { "chain": "etc" }]),
+        );
+        assert!(res.unwrap_err().contains("Invalid passphrase"));
+    }
+
+    #[test]
+    fn should_restore_latest_deleted_copy() {
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let storage = storage_ctrl.get_keystore("etc").unwrap();
+        let kf = keyfile();
+        let account = json!({ "address": kf.address.to_string(), "passphrase": "1234567890" });
+
+        storage.put(&kf).unwrap();
+        call(
+            &io,
+            "signer_deleteAccount",
+            json!([account, { "chain": "etc" }]),
+        )
+        .unwrap();
+        storage.put(&kf).unwrap();
+        storage
+            .update(&kf.address, Some("renamed".to_string()), None)
+            .unwrap();
+        call(
+            &io,
+            "signer_deleteAccount",
+            json!([account, { "chain": "etc" }]),
+        )
+        .unwrap();
+
+        let res = call(
+            &io,
+            "signer_listDeletedAccounts",
+            json!([{ "chain": "etc" }]),
+        )
+        .unwrap();
+        assert_eq!(res.as_array().unwrap().len(), 2);
+        assert!(res[0]["deleted_at"].as_i64() < res[1]["deleted_at"].as_i64());
+        assert_eq!(res[1]["name"], "renamed");
+
+        let res = call(
+            &io,
+            "signer_restoreAccount",
+            json!([{ "address": kf.address.to_string() }, { "chain": "etc" }]),
+        );
+        assert_eq!(res, Ok(json!(true)));
+        let (_, restored) = storage.search_by_address(&kf.address).unwrap();
+        assert_eq!(restored.name, Some("renamed".to_string()));
+
+        let res = call(
+            &io,
+            "signer_purgeAccount",
+            json!([account, { "chain": "etc" }]),
+        );
+        assert_eq!(res, Ok(json!(true)));
+        let res = call(
+            &io,
+            "signer_listDeletedAccounts",
+            json!([{ "chain": "etc" }]),
+        );
+        assert_eq!(res, Ok(json!([])));
+    }
+
+    #[test]
+    fn should_reject_odd_length_create2_init_code() {
+        let io = handler();
+        let params = json!({
+            "deployer": "0x0000000000000000000000000000000000000000",
+            "salt": format!("0x{}", "0".repeat(64)),
+            "initCode": "0x00f"
+        });
+
+        let res = call(&io, "signer_computeCreate2Address", json!([params]));
+        assert!(res.unwrap_err().contains("Odd length"));
+    }
+
+    #[test]
+    fn should_skip_logs_with_oversized_array_length() {
+        let io = handler();
+        let abi = json!([{
+            "anonymous": false,
+            "inputs": [{ "indexed": false, "name": "ids", "type": "uint256[]" }],
+            "name": "Batch",
+            "type": "event"
+        }]);
+        for i in 1..4 {
+            let addr = format!("0x{:040x}", i);
+            call(
+                &io,
+                "signer_importContract",
+                json!([{ "address": addr, "name": format!("Batch{}", i), "abi": abi }, { "chain": "etc" }]),
+            )
+            .unwrap();
+        }
+
+        let log = json!({
+            "topics": [format!("0x{}", hex::encode(crate::util::keccak256(b"Batch(uint256[])")))],
+            "data": format!("0x{:064x}{:064x}", 0x20, 0xffff_ffffu32)
+        });
+        let res = call(&io, "signer_decodeLogs", json!([[log], { "chain": "etc" }]));
+        assert_eq!(res, Ok(json!([null])));
+    }
+
+    #[test]
+    fn should_record_peer_into_audit_log() {
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let admin = build_admin_handler(Arc::clone(&storage_ctrl));
+        let kf = keyfile();
+        storage_ctrl.get_keystore("etc").unwrap().put(&kf).unwrap();
+
+        let meta = Meta {
+            peer: Some("127.0.0.1:50000".parse().unwrap()),
+        };
+        let additional = json!({ "chain": "etc", "caller": "wallet" });
+        let params = json!(["0xabcd", kf.address.to_string(), "1234567890", additional]);
+        call_with_meta(&io, "signer_sign", params, meta).unwrap();
+
+        assert!(call(&io, "signer_listAuditLog", json!([{}, { "chain": "etc" }])).is_err());
+        let entries = call(
+            &admin,
+            "signer_listAuditLog",
+            json!([{}, { "chain": "etc" }]),
+        )
+        .unwrap();
+        assert_eq!(entries[0]["caller"], "wallet");
+        assert_eq!(entries[0]["peer"], "127.0.0.1:50000");
+        let res = call(&admin, "signer_verifyAuditLog", json!([{ "chain": "etc" }])).unwrap();
+        assert_eq!(res["valid"], true);
+    }
+
+    #[test]
+    fn should_reject_writes_to_shared_account() {
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let kf = keyfile();
+        storage_ctrl.get_keystore("etc").unwrap().put(&kf).unwrap();
+        storage_ctrl
+            .get_trash("eth")
+            .unwrap()
+            .put(&kf, "old")
+            .unwrap();
+        let account = json!({ "address": kf.address.to_string(), "chains": ["eth"] });
+        call(
+            &io,
+            "signer_linkAccount",
+            json!([account, { "chain": "etc" }]),
+        )
+        .unwrap();
+
+        let raw = serde_json::to_value(&kf).unwrap();
+        let res = call(
+            &io,
+            "signer_importAccount",
+            json!([raw, { "chain": "eth" }]),
+        );
+        assert!(res.unwrap_err().contains("is shared from chain etc"));
+
+        let account = json!({ "address": kf.address.to_string() });
+        let res = call(
+            &io,
+            "signer_restoreAccount",
+            json!([account, { "chain": "eth" }]),
+        );
+        assert!(res.unwrap_err().contains("is shared from chain etc"));
+        assert!(storage_ctrl
+            .get_keystore("eth")
+            .unwrap()
+            .search_by_address(&kf.address)
+            .is_err());
+    }
+
+    #[test]
+    fn should_reject_keyfile_import_over_watch_only() {
+        use crate::storage::WatchOnlyAccount;
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let storage = storage_ctrl.get_keystore("etc").unwrap();
+        let kf = keyfile();
+        storage
+            .put_watch_only(&WatchOnlyAccount {
+                address: kf.address,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let raw = serde_json::to_value(&kf).unwrap();
+        let res = call(
+            &io,
+            "signer_importAccount",
+            json!([raw, { "chain": "etc" }]),
+        );
+        assert!(res.unwrap_err().contains("Watch-only"));
+        assert!(storage.search_by_address(&kf.address).is_err());
+        assert!(storage.is_watch_only(&kf.address));
+    }
+
+    #[test]
+    fn should_checksum_listed_addresses_on_request() {
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let kf = keyfile();
+        storage_ctrl.get_keystore("etc").unwrap().put(&kf).unwrap();
+        let chain = storage_ctrl.chains().find("etc").unwrap();
+
+        let res = call(&io, "signer_listAccounts", json!([{ "chain": "etc" }])).unwrap();
+        assert_eq!(res[0]["address"], kf.address.to_string());
+
+        let additional = json!({ "chain": "etc", "checksum": true });
+        let res = call(&io, "signer_listAccounts", json!([additional])).unwrap();
+        assert_eq!(res[0]["address"], chain.format_address(&kf.address));
+    }
+
+    #[test]
+    fn should_preview_token_transfer() {
+        let io = handler();
+        let from = "0x0000000000000000000000000000000000000002";
+        let token = "0x00000000000000000000000000000000000000aa";
+        let abi: Value = serde_json::from_str(ABI).unwrap();
+        call(
+            &io,
+            "signer_importWatchOnly",
+            json!([{ "address": from, "name": "Alice" }, { "chain": "etc" }]),
+        )
+        .unwrap();
+        call(
+            &io,
+            "signer_importContract",
+            json!([{ "address": token, "name": "Token", "abi": abi }, { "chain": "etc" }]),
+        )
+        .unwrap();
+
+        let tx = json!({
+            "from": from,
+            "to": token,
+            "gas": "0x186a0",
+            "gasPrice": "0x3b9aca00",
+            "value": "0x0",
+            "data": TRANSFER,
+            "nonce": "0x0"
+        });
+        let res = call(
+            &io,
+            "signer_previewTransaction",
+            json!([tx, { "chain": "etc" }]),
+        )
+        .unwrap();
+        assert_eq!(res["from_label"], "Alice");
+        assert_eq!(res["to_label"], "Token");
+        assert_eq!(res["call"]["signature"], "transfer(address,uint256)");
+        assert_eq!(res["call"]["params"][1]["value"], "10");
+        // 21000 + 6 non-zero * 16 + 62 zero * 4 bytes of calldata, at 1 gwei
+        assert_eq!(res["fee"]["min"], "0.000021344");
+        assert_eq!(res["fee"]["max"], "0.0001");
+    }
+
+    #[test]
+    fn should_preview_call_with_oversized_array_length() {
+        let io = handler();
+        let to = "0x00000000000000000000000000000000000000aa";
+        let abi = json!([{
+            "constant": false,
+            "inputs": [{ "name": "ids", "type": "uint256[]" }],
+            "name": "batch",
+            "outputs": [],
+            "payable": false,
+            "type": "function"
+        }]);
+        call(
+            &io,
+            "signer_importContract",
+            json!([{ "address": to, "name": "Batch", "abi": abi }, { "chain": "etc" }]),
+        )
+        .unwrap();
+
+        let selector = &crate::util::keccak256(b"batch(uint256[])")[..4];
+        let tx = json!({
+            "from": "0x0000000000000000000000000000000000000002",
+            "to": to,
+            "gas": "0x186a0",
+            "gasPrice": "0x3b9aca00",
+            "data": format!("0x{}{:064x}{:064x}", hex::encode(selector), 0x20, 0xffff_ffffu32),
+            "nonce": "0x0"
+        });
+        let res = call(
+            &io,
+            "signer_previewTransaction",
+            json!([tx, { "chain": "etc" }]),
+        )
+        .unwrap();
+        assert_eq!(res["to_label"], "Batch");
+        assert!(res.get("call").is_none());
+    }
+
+    #[test]
+    fn should_reject_preview_with_overflowing_fee() {
+        let io = handler();
+        let tx = json!({
+            "from": "0x0000000000000000000000000000000000000002",
+            "to": "0x0000000000000000000000000000000000000003",
+            "gas": "0x5208",
+            "gasPrice": format!("0x{}", "f".repeat(64)),
+            "nonce": "0x0"
+        });
+
+        let res = call(
+            &io,
+            "signer_previewTransaction",
+            json!([tx, { "chain": "etc" }]),
+        );
+        assert!(res.unwrap_err().contains("256 bits"));
+    }
+
+    #[test]
+    fn should_sign_with_keyfile_also_stored_as_watch_only() {
+        use crate::storage::WatchOnlyAccount;
+        use crate::tests::keyfile;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+        let storage = storage_ctrl.get_keystore("etc").unwrap();
+        let kf = keyfile();
+        let params = json!(["hello", kf.address.to_string(), "1234567890", { "chain": "etc" }]);
+
+        storage
+            .put_watch_only(&WatchOnlyAccount {
+                address: kf.address,
+                ..Default::default()
+            })
+            .unwrap();
+        let res = call(&io, "signer_sign", params.clone());
+        assert!(res.unwrap_err().contains("Watch-only"));
+
+        storage.put(&kf).unwrap();
+        assert!(call(&io, "signer_sign", params).is_ok());
+    }
+
+    #[test]
+    fn should_skip_accounts_shared_from_unknown_chain() {
+        use crate::tests::test_address;
+
+        let storage_ctrl = Arc::new(storage_ctrl());
+        storage_ctrl
+            .links()
+            .link(&test_address(), "removed", &["etc".to_string()])
+            .unwrap();
+        let io = build_handler(Arc::clone(&storage_ctrl), KdfDepthLevel::default());
+
+        let res = call(&io, "signer_listAccounts", json!([{ "chain": "etc" }]));
+        assert_eq!(res, Ok(json!([])));
+    }
+
+    #[test]
+    fn should_update_contract_found_by_name() {
+        let io = handler();
+        let addr = "0x00000000000000000000000000000000000000aa";
+        let abi: Value = serde_json::from_str(ABI).unwrap();
+        call(
+            &io,
+            "signer_importContract",
+            json!([{ "address": addr, "name": "Token", "abi": abi }, { "chain": "etc" }]),
+        )
+        .unwrap();
+
+        let res = call(
+            &io,
+            "signer_updateContract",
+            json!([{ "name": "Token" }, { "name": "Renamed" }, { "chain": "etc" }]),
+        );
+        assert_eq!(res, Ok(json!(true)));
+
+        let res = call(
+            &io,
+            "signer_exportContract",
+            json!([{ "address": addr }, { "chain": "etc" }]),
+        )
+        .unwrap();
+        assert_eq!(res["name"], "Renamed");
+    }
 }
@@ -17,12 +17,17 @@ impl SignTxTransaction {
         let value = Vec::from_hex(v_str)?;
         let nonce = Vec::from_hex(to_even_str(trim_hex(self.nonce.as_str())))?;
         let data = to_even_str(trim_hex(self.data.as_str()));
+        // empty recipient means contract creation
+        let to = match self.to {
+            Some(ref to) if !trim_hex(to).is_empty() => Some(to.parse::<Address>()?),
+            _ => None,
+        };
 
         Ok(Transaction {
             nonce: to_u64(&nonce),
             gas_price: to_arr(&align_bytes(&gas_price, 32)),
             gas_limit: to_u64(&gas_limit),
-            to: self.to.as_str().parse::<Address>().ok(),
+            to,
             value: to_arr(&align_bytes(&value, 32)),
             data: Vec::from_hex(data)?,
         })
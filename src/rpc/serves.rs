@@ -1,24 +1,119 @@
 use super::common::{
-    extract_chain_params, CommonAdditional, Either, FunctionParams, ListAccountAccount,
-    ListAccountsAdditional, NewAccountAccount, SelectedAccount, ShakeAccountAccount, SignParams,
-    SignTxAdditional, SignTxParams, SignTxTransaction, UpdateAccountAccount,
+    extract_chain_params, CommonAdditional, CommonChainParams, ContractQuery, Create2Address,
+    Create2Params, DecodeCallParams, DecodeLogsAdditional, DecodeResultParams, DecodedFunctionCall,
+    DecodedLog, DeleteAccountAccount, DeletedAccount, DeployTransaction, Deployment,
+    DerivedAddress, Either, FeeBounds, FunctionCallParams, FunctionParams, LinkAccountAccount,
+    ListAccountAccount, ListAccountsAdditional, LogParams, MnemonicRangeAccounts,
+    NewAccountAccount, NftTransferParams, SelectedAccount, ShakeAccountAccount, SignParams,
+    SignTxAdditional, SignTxParams, SignTxTransaction, TokenTransfer, TokenTransferParams,
+    TransactionPreview, UpdateAccountAccount, UpdateContractParams,
 };
 use super::Error;
+use super::Meta;
 use super::StorageController;
-use crate::contract::Contract;
+use crate::chains::ChainInfo;
+use crate::contract::{self, Contract, DecodedCall};
 use crate::core::{Address, Transaction};
-use crate::keystore::{CryptoType, Kdf, KdfDepthLevel, KeyFile, PBKDF2_KDF_NAME};
+use crate::keystore::{
+    CoreCrypto, CryptoType, Kdf, KdfDepthLevel, KdfParams, KeyFile, PBKDF2_KDF_NAME,
+};
 use crate::mnemonic::{gen_entropy, hd_path, HDPath, Language, Mnemonic, ENTROPY_BYTE_LENGTH};
-use crate::rpc::common::{NewMnemonicAccount, SignTypedDataParams};
+use crate::rpc::common::{
+    ImportSeedAccount, ImportXpubAccount, NewMnemonicAccount, SeedInfo, SelectedSeed,
+    SignTypedDataParams, UnsignedTransaction, WatchOnlyAccountParams, XpubAddress,
+    XpubAddressesParams,
+};
+use crate::storage::{
+    AuditEntry, AuditFilter, AuditRecord, AuditVerification, ContractStorage, KeyfileStorage,
+    KeystoreError, SeedAccount, WatchOnlyAccount, XpubAccount,
+};
 use crate::util;
 use jsonrpc_core::{Params, Value};
+use rand::Rng;
 use serde_json;
 use std::ops::Deref;
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
+use uuid::Uuid;
 
 static OPENRPC_SCHEMA: &[u8] = include_bytes!("../../openrpc.json");
 
+/// Max number of addresses derived in a single request
+const MAX_DERIVE_COUNT: u32 = 1000;
+
+/// Max number of accounts imported in a single request
+const MAX_IMPORT_COUNT: u32 = 100;
+
+/// Placeholder for address index in account name template
+const NAME_TEMPLATE_INDEX: &str = "{index}";
+
+/// Caller identity recorded into audit log, if not specified in request.
+/// Identity is self-declared by client and not authenticated in any way.
+const ANONYMOUS_CALLER: &str = "anonymous";
+
+/// Refuse signing with accounts held without private key, or storing
+/// a `KeyFile` over them. For signing it's checked only if there is no `KeyFile` for the address.
+fn check_not_watch_only(storage: &dyn KeyfileStorage, addr: &Address) -> Result<(), Error> {
+    if storage.is_watch_only(addr) {
+        return Err(Error::WatchOnlyAccount(addr.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Refuse changing accounts shared into the chain from another one,
+/// they are managed on their home chain
+fn check_not_linked(
+    storage_ctrl: &StorageController,
+    chain: &str,
+    addr: &Address,
+) -> Result<(), Error> {
+    if let Some(home) = storage_ctrl.links().home_chain(chain, addr) {
+        return Err(Error::InvalidDataFormat(format!(
+            "Account {} is shared from chain {}, unlink it instead",
+            addr, home
+        )));
+    }
+
+    Ok(())
+}
+
+/// Ensure `KeyFile` wasn't modified by another request
+/// while passphrase was being checked outside of account lock
+fn check_unchanged(storage: &dyn KeyfileStorage, kf: &KeyFile) -> Result<(), Error> {
+    match storage.search_by_address(&kf.address) {
+        Ok((_, ref current)) if current == kf => Ok(()),
+        _ => Err(Error::StorageError(format!(
+            "Account was modified concurrently: {}",
+            kf.address
+        ))),
+    }
+}
+
+/// Record signing operation into audit log of the chain
+fn audit(
+    storage_ctrl: &StorageController,
+    method: &str,
+    chain: &str,
+    addr: &Address,
+    payload_hash: &[u8],
+    caller: &Option<String>,
+    meta: &Meta,
+) -> Result<(), Error> {
+    let entry = storage_ctrl.get_audit_log(chain)?.append(AuditRecord {
+        method: method.to_string(),
+        account: addr.to_string(),
+        chain: chain.to_string(),
+        payload_hash: format!("0x{}", hex::encode(payload_hash)),
+        caller: caller
+            .clone()
+            .unwrap_or_else(|| ANONYMOUS_CALLER.to_string()),
+        peer: meta.peer.map(|a| a.to_string()),
+    })?;
+    log::debug!("Audit log entry {} recorded: {}", entry.index, entry.hash);
+
+    Ok(())
+}
+
 pub fn openrpc_discover() -> Result<String, Error> {
     let contents = String::from_utf8_lossy(OPENRPC_SCHEMA).deref().to_string();
 
@@ -27,23 +122,48 @@ pub fn openrpc_discover() -> Result<String, Error> {
 
 pub fn list_accounts(
     params: Either<(), (ListAccountsAdditional,)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<Vec<ListAccountAccount>, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (additional,) = params.into_right();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let storage = storage_ctrl.get_keystore(&chain)?;
-    let res = storage
+    let mut res: Vec<ListAccountAccount> = storage
         .list_accounts(additional.show_hidden)?
         .iter()
-        .map(|info| ListAccountAccount {
-            name: info.name.clone(),
-            address: info.address.clone(),
-            description: info.description.clone(),
-            hardware: info.is_hardware,
-            is_hidden: info.is_hidden,
-        })
+        .map(ListAccountAccount::from)
         .collect();
+
+    for (addr, home) in storage_ctrl.links().linked_to(&chain) {
+        let storage = match storage_ctrl.get_keystore(&home) {
+            Ok(storage) => storage,
+            Err(_) => {
+                log::warn!(
+                    "Skipped account {} shared from unknown chain {}",
+                    addr,
+                    home
+                );
+                continue;
+            }
+        };
+        let info = match storage.search_by_address(&addr) {
+            Ok((info, _)) => info,
+            Err(_) => continue,
+        };
+        if !info.is_hidden || additional.show_hidden {
+            let mut account = ListAccountAccount::from(&info);
+            account.shared_from = Some(home);
+            res.push(account);
+        }
+    }
+    if additional.checksum {
+        if let Some(info) = storage_ctrl.chains().find(&chain) {
+            for account in res.iter_mut() {
+                if let Ok(addr) = Address::from_str(&account.address) {
+                    account.address = info.format_address(&addr);
+                }
+            }
+        }
+    }
     log::debug!(
         "Accounts listed with `show_hidden`: {}\n\t{:?}",
         additional.show_hidden,
@@ -55,13 +175,14 @@ pub fn list_accounts(
 
 pub fn hide_account(
     params: Either<(SelectedAccount,), (SelectedAccount, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<bool, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&account.address)?;
+    let (home, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
+    let lock = storage_ctrl.account_lock(&home, &addr);
+    let _guard = lock.lock().unwrap();
     let res = storage.hide(&addr)?;
     log::debug!("Account hided: {}", addr);
 
@@ -70,13 +191,14 @@ pub fn hide_account(
 
 pub fn unhide_account(
     params: Either<(SelectedAccount,), (SelectedAccount, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<bool, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&account.address)?;
+    let (home, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
+    let lock = storage_ctrl.account_lock(&home, &addr);
+    let _guard = lock.lock().unwrap();
     let res = storage.unhide(&addr)?;
     log::debug!("Account unhided: {}", addr);
 
@@ -85,17 +207,16 @@ pub fn unhide_account(
 
 pub fn shake_account(
     params: Either<(ShakeAccountAccount,), (ShakeAccountAccount, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<bool, Error> {
     use crate::util::os_random;
 
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&account.address)?;
+    let (home, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
 
-    let (_, kf) = storage.search_by_address(&addr)?;
+    let (info, kf) = storage.search_by_address(&addr)?;
     match kf.crypto {
         CryptoType::Core(ref core) => {
             let pk = kf.decrypt_key(&account.old_passphrase)?;
@@ -104,10 +225,15 @@ pub fn shake_account(
                 &account.new_passphrase,
                 core.kdf_params.kdf,
                 &mut os_random(),
-                kf.name,
-                kf.description,
+                kf.name.clone(),
+                kf.description.clone(),
             )?;
-            storage.put(&new_kf)?;
+
+            let lock = storage_ctrl.account_lock(&home, &addr);
+            let _guard = lock.lock().unwrap();
+            check_unchanged(storage, &kf)?;
+            // replace in place, so interrupted write can't leave two keyfiles
+            storage.put_with_name(&new_kf, &info.filename)?;
             log::debug!("Account shaked: {}", kf.address);
         }
     };
@@ -117,15 +243,16 @@ pub fn shake_account(
 
 pub fn update_account(
     params: Either<(UpdateAccountAccount,), (UpdateAccountAccount, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<bool, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&account.address)?;
+    let (home, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
 
-    let (_, mut kf) = storage.search_by_address(&addr)?;
+    let lock = storage_ctrl.account_lock(&home, &addr);
+    let _guard = lock.lock().unwrap();
+    let (info, mut kf) = storage.search_by_address(&addr)?;
     if !account.name.is_empty() {
         kf.name = Some(account.name);
     }
@@ -133,7 +260,7 @@ pub fn update_account(
         kf.description = Some(account.description);
     }
 
-    storage.put(&kf)?;
+    storage.put_with_name(&kf, &info.filename)?;
     log::debug!(
         "Account {} updated with name: {}, description: {}",
         kf.address,
@@ -144,17 +271,189 @@ pub fn update_account(
     Ok(true)
 }
 
+pub fn delete_account(
+    params: Either<(DeleteAccountAccount,), (DeleteAccountAccount, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_keystore(&chain)?;
+    let trash = storage_ctrl.get_trash(&chain)?;
+    let addr = Address::from_str(&account.address)?;
+    check_not_linked(storage_ctrl, &chain, &addr)?;
+    let (info, kf) = match storage.search_by_address(&addr) {
+        Ok(found) => found,
+        Err(e) => {
+            check_not_watch_only(storage, &addr)?;
+            return Err(e.into());
+        }
+    };
+    kf.decrypt_key(&account.passphrase)?;
+
+    let lock = storage_ctrl.account_lock(&chain, &addr);
+    let _guard = lock.lock().unwrap();
+    check_unchanged(storage, &kf)?;
+    trash.put(&kf, &info.filename)?;
+    storage.delete(&addr)?;
+    storage_ctrl.links().remove(&addr)?;
+    log::debug!("Account moved to trash: {}", addr);
+
+    Ok(true)
+}
+
+pub fn list_deleted_accounts(
+    params: Either<(), (CommonAdditional,)>,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<DeletedAccount>, Error> {
+    let (additional,) = params.into_right();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let trash = storage_ctrl.get_trash(&chain)?;
+
+    let res = trash.list().iter().map(DeletedAccount::from).collect();
+    log::debug!("Deleted accounts listed: {:?}", res);
+
+    Ok(res)
+}
+
+pub fn restore_account(
+    params: Either<(SelectedAccount,), (SelectedAccount, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_keystore(&chain)?;
+    let trash = storage_ctrl.get_trash(&chain)?;
+    let addr = Address::from_str(&account.address)?;
+
+    let lock = storage_ctrl.account_lock(&chain, &addr);
+    let _guard = lock.lock().unwrap();
+    check_not_linked(storage_ctrl, &chain, &addr)?;
+    let entry = trash.latest(&addr)?;
+    if storage.search_by_address(&addr).is_ok() || storage.is_watch_only(&addr) {
+        return Err(Error::InvalidDataFormat(format!(
+            "Account already exists: {}",
+            addr
+        )));
+    }
+
+    storage.put_with_name(&entry.keyfile, &entry.filename)?;
+    trash.remove(&addr, entry.deleted_at)?;
+    log::debug!(
+        "Account restored from trash: {}, deleted at: {}",
+        addr,
+        entry.deleted_at
+    );
+
+    Ok(true)
+}
+
+pub fn purge_account(
+    params: Either<(DeleteAccountAccount,), (DeleteAccountAccount, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let trash = storage_ctrl.get_trash(&chain)?;
+    let addr = Address::from_str(&account.address)?;
+
+    let entries = trash.find(&addr);
+    if entries.is_empty() {
+        return Err(KeystoreError::NotFound(addr.to_string()).into());
+    }
+    // every copy may be encrypted with its own passphrase
+    let purged: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| entry.keyfile.decrypt_key(&account.passphrase).is_ok())
+        .collect();
+    if purged.is_empty() {
+        return Err(Error::InvalidDataFormat("Invalid passphrase".to_string()));
+    }
+
+    let lock = storage_ctrl.account_lock(&chain, &addr);
+    let _guard = lock.lock().unwrap();
+    for entry in &purged {
+        trash.remove(&addr, entry.deleted_at)?;
+    }
+    log::debug!("Account purged: {}, copies: {}", addr, purged.len());
+
+    Ok(true)
+}
+
+pub fn link_account(
+    params: Either<(LinkAccountAccount,), (LinkAccountAccount, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_keystore(&chain)?;
+    let addr = Address::from_str(&account.address)?;
+
+    let mut chains = Vec::with_capacity(account.chains.len());
+    for c in &account.chains {
+        let name = storage_ctrl.chain(c)?.name.clone();
+        let target = storage_ctrl.get_keystore(&name)?;
+        if name != chain && (target.search_by_address(&addr).is_ok() || target.is_watch_only(&addr))
+        {
+            return Err(Error::InvalidDataFormat(format!(
+                "Account {} already exists on chain: {}",
+                addr, name
+            )));
+        }
+        chains.push(name);
+    }
+
+    let lock = storage_ctrl.account_lock(&chain, &addr);
+    let _guard = lock.lock().unwrap();
+    storage.search_by_address(&addr)?;
+    storage_ctrl.links().link(&addr, &chain, &chains)?;
+    log::debug!("Account {} of {} shared with: {:?}", addr, chain, chains);
+
+    Ok(true)
+}
+
+pub fn unlink_account(
+    params: Either<(LinkAccountAccount,), (LinkAccountAccount, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let addr = Address::from_str(&account.address)?;
+
+    let link = storage_ctrl
+        .links()
+        .get(&addr)
+        .filter(|link| link.chain == chain)
+        .ok_or_else(|| {
+            Error::InvalidDataFormat(format!("Account {} isn't shared from: {}", addr, chain))
+        })?;
+
+    let mut chains = Vec::with_capacity(account.chains.len());
+    for c in &account.chains {
+        chains.push(storage_ctrl.chain(c)?.name.clone());
+    }
+
+    let lock = storage_ctrl.account_lock(&link.chain, &addr);
+    let _guard = lock.lock().unwrap();
+    storage_ctrl.links().unlink(&addr, &chains)?;
+    log::debug!("Account {} of {} unshared from: {:?}", addr, chain, chains);
+
+    Ok(true)
+}
+
 pub fn import_account(
     params: Either<(Value,), (Value, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<String, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (raw, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let storage = storage_ctrl.get_keystore(&chain)?;
     let raw = serde_json::to_string(&raw)?;
 
     let kf = KeyFile::decode(&raw)?;
+    let lock = storage_ctrl.account_lock(&chain, &kf.address);
+    let _guard = lock.lock().unwrap();
+    check_not_linked(storage_ctrl, &chain, &kf.address)?;
+    check_not_watch_only(storage, &kf.address)?;
     storage.put(&kf)?;
 
     log::debug!("Account imported: {}", kf.address);
@@ -164,13 +463,12 @@ pub fn import_account(
 
 pub fn export_account(
     params: Either<(SelectedAccount,), (SelectedAccount, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<Value, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&account.address)?;
+    let (_, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
 
     let (_, kf) = storage.search_by_address(&addr)?;
     let value = serde_json::to_value(&kf)?;
@@ -182,11 +480,10 @@ pub fn export_account(
 pub fn new_account(
     params: Either<(NewAccountAccount,), (NewAccountAccount, CommonAdditional)>,
     sec: KdfDepthLevel,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<String, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let storage = storage_ctrl.get_keystore(&chain)?;
     if account.passphrase.is_empty() {
         return Err(Error::InvalidDataFormat("Empty passphrase".to_string()));
@@ -200,25 +497,34 @@ pub fn new_account(
     )?;
 
     let addr = kf.address.to_string();
+    let lock = storage_ctrl.account_lock(&chain, &kf.address);
+    let _guard = lock.lock().unwrap();
+    check_not_linked(storage_ctrl, &chain, &kf.address)?;
+    check_not_watch_only(storage, &kf.address)?;
     storage.put(&kf)?;
     log::debug!("New account generated: {}", kf.address);
 
     Ok(addr)
 }
 
-pub fn sign_transaction(
-    params: SignTxParams<
-        (SignTxTransaction, String),
-        (SignTxTransaction, String, SignTxAdditional),
-    >,
-    storage: &Arc<Mutex<StorageController>>,
-) -> Result<Params, Error> {
-    let storage_ctrl = storage.lock().unwrap();
-    let (transaction, passphrase, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+/// Sign transaction with key of a stored account and record it into audit log
+///
+/// # Return:
+///
+/// RLP encoded signed transaction
+///
+fn sign_raw_transaction(
+    storage_ctrl: &StorageController,
+    transaction: SignTxTransaction,
+    passphrase: &str,
+    chain: &str,
+    chain_id: u64,
+    method: &str,
+    caller: &Option<String>,
+    meta: &Meta,
+) -> Result<Vec<u8>, Error> {
     let addr = Address::from_str(&transaction.from)?;
-    let (_chain, chain_id) = extract_chain_params(&additional)?;
+    let (_, storage) = storage_ctrl.resolve_keystore(chain, &addr)?;
 
     match storage.search_by_address(&addr) {
         Ok((_, kf)) => match transaction.try_into() {
@@ -227,14 +533,22 @@ pub fn sign_transaction(
                     return Err(Error::InvalidDataFormat("Missing passphrase".to_string()));
                 }
 
-                if let Ok(pk) = kf.decrypt_key(&passphrase) {
+                if let Ok(pk) = kf.decrypt_key(passphrase) {
                     let raw = tr
                         .to_signed_raw(pk, chain_id)
                         .expect("Expect to sign a transaction");
-                    let signed = Transaction::signed_rpl_into_raw_params(&raw);
-                    log::debug!("Signed transaction to: {:?}\n\t raw: {:?}", &tr.to, signed);
+                    audit(
+                        storage_ctrl,
+                        method,
+                        chain,
+                        &addr,
+                        &tr.hash(chain_id),
+                        caller,
+                        meta,
+                    )?;
+                    log::debug!("Signed transaction to: {:?}", &tr.to);
 
-                    Ok(signed)
+                    Ok(raw)
                 } else {
                     Err(Error::InvalidDataFormat("Invalid passphrase".to_string()))
                 }
@@ -242,22 +556,106 @@ pub fn sign_transaction(
             Err(err) => Err(Error::InvalidDataFormat(err.to_string())),
         },
 
-        Err(_) => Err(Error::InvalidDataFormat("Can't find account".to_string())),
+        Err(_) => {
+            check_not_watch_only(storage, &addr)?;
+            Err(Error::InvalidDataFormat("Can't find account".to_string()))
+        }
     }
 }
 
+pub fn sign_transaction(
+    params: SignTxParams<
+        (SignTxTransaction, String),
+        (SignTxTransaction, String, SignTxAdditional),
+    >,
+    storage_ctrl: &StorageController,
+    meta: &Meta,
+) -> Result<Params, Error> {
+    let (transaction, passphrase, additional) = params.into_full();
+    let (chain, chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+
+    let raw = sign_raw_transaction(
+        storage_ctrl,
+        transaction,
+        &passphrase,
+        &chain,
+        chain_id,
+        "signer_signTransaction",
+        &additional.caller,
+        meta,
+    )?;
+    let signed = Transaction::signed_rpl_into_raw_params(&raw);
+    log::debug!("Signed transaction raw: {:?}", signed);
+
+    Ok(signed)
+}
+
+pub fn sign_deployment(
+    params: SignTxParams<
+        (DeployTransaction, String),
+        (DeployTransaction, String, SignTxAdditional),
+    >,
+    storage_ctrl: &StorageController,
+    meta: &Meta,
+) -> Result<Deployment, Error> {
+    let (deploy, passphrase, additional) = params.into_full();
+    let (chain, chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let mut transaction = deploy.transaction;
+
+    if transaction
+        .to
+        .as_ref()
+        .map_or(false, |to| !util::trim_hex(to).is_empty())
+    {
+        return Err(Error::InvalidDataFormat(
+            "Deployment transaction can't have a recipient".to_string(),
+        ));
+    }
+    let mut code = decode_hex(&transaction.data)?;
+    if code.is_empty() {
+        return Err(Error::InvalidDataFormat(
+            "Missing contract bytecode".to_string(),
+        ));
+    }
+    if let Some(ref args) = deploy.constructor_args {
+        code.extend(decode_hex(args)?);
+    }
+    transaction.data = hex::encode(code);
+
+    let from = Address::from_str(&transaction.from)?;
+    let nonce = transaction.clone().try_into()?.nonce;
+    let raw = sign_raw_transaction(
+        storage_ctrl,
+        transaction,
+        &passphrase,
+        &chain,
+        chain_id,
+        "signer_signDeployment",
+        &additional.caller,
+        meta,
+    )?;
+    let address = from.contract_address(nonce);
+    log::debug!("Signed deployment of contract: {}", address);
+
+    Ok(Deployment {
+        signed: format!("0x{}", hex::encode(raw)),
+        address: address.to_string(),
+    })
+}
+
 pub fn sign(
     params: SignParams<(String, String, String, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
+    meta: &Meta,
 ) -> Result<Params, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (input, address, passphrase, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&address)?;
+    let (_, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
     let hash = util::keccak256(
         format!("\x19Ethereum Signed Message:\n{}{}", input.len(), input).as_bytes(),
     );
+
     match storage.search_by_address(&addr) {
         Ok((_, kf)) => {
             if passphrase.is_empty() {
@@ -265,24 +663,36 @@ pub fn sign(
             }
             if let Ok(pk) = kf.decrypt_key(&passphrase) {
                 let signed = pk.sign_hash(hash)?;
+                audit(
+                    storage_ctrl,
+                    "signer_sign",
+                    &chain,
+                    &addr,
+                    &hash,
+                    &additional.caller,
+                    meta,
+                )?;
                 Ok(Params::Array(vec![Value::String(signed.into())]))
             } else {
                 Err(Error::InvalidDataFormat("Invalid passphrase".to_string()))
             }
         }
-        Err(_) => Err(Error::InvalidDataFormat("Can't find account".to_string())),
+        Err(_) => {
+            check_not_watch_only(storage, &addr)?;
+            Err(Error::InvalidDataFormat("Can't find account".to_string()))
+        }
     }
 }
 
 pub fn sign_typed_data(
     params: SignTypedDataParams<(String, Value, String, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
+    meta: &Meta,
 ) -> Result<Params, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (address, typed_data, passphrase, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
-    let storage = storage_ctrl.get_keystore(&chain)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let addr = Address::from_str(&address)?;
+    let (_, storage) = storage_ctrl.resolve_keystore(&chain, &addr)?;
 
     let hash =
         util::typed::hash(typed_data).map_err(|err| Error::TypedDataError(err.to_string()))?;
@@ -294,30 +704,535 @@ pub fn sign_typed_data(
             }
             if let Ok(pk) = kf.decrypt_key(&passphrase) {
                 let signed = pk.sign_hash(hash)?;
+                audit(
+                    storage_ctrl,
+                    "signer_signTypedData",
+                    &chain,
+                    &addr,
+                    &hash,
+                    &additional.caller,
+                    meta,
+                )?;
                 Ok(Params::Array(vec![Value::String(signed.into())]))
             } else {
                 Err(Error::InvalidDataFormat("Invalid passphrase".to_string()))
             }
         }
-        Err(_) => Err(Error::InvalidDataFormat("Can't find account".to_string())),
+        Err(_) => {
+            check_not_watch_only(storage, &addr)?;
+            Err(Error::InvalidDataFormat("Can't find account".to_string()))
+        }
+    }
+}
+
+pub fn import_watch_only(
+    params: Either<(WatchOnlyAccountParams,), (WatchOnlyAccountParams, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<String, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_keystore(&chain)?;
+    let addr = Address::from_str(&account.address)?;
+
+    let lock = storage_ctrl.account_lock(&chain, &addr);
+    let _guard = lock.lock().unwrap();
+    if storage.search_by_address(&addr).is_ok() {
+        return Err(Error::InvalidDataFormat(format!(
+            "Account {} is already stored with a private key",
+            addr
+        )));
+    }
+
+    storage.put_watch_only(&WatchOnlyAccount {
+        address: addr,
+        name: account.name,
+        description: account.description,
+        tags: account.tags,
+    })?;
+    log::debug!("Watch-only account imported: {}", addr);
+
+    Ok(addr.to_string())
+}
+
+pub fn build_transaction(
+    params: Either<(SignTxTransaction,), (SignTxTransaction, SignTxAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<UnsignedTransaction, Error> {
+    let (transaction, additional) = params.into_full();
+    let (chain, chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_keystore(&chain)?;
+    let addr = Address::from_str(&transaction.from)?;
+
+    if !storage.is_watch_only(&addr) {
+        storage.is_addr_exist(&addr)?;
+    }
+
+    let tr = transaction
+        .try_into()
+        .map_err(|err| Error::InvalidDataFormat(err.to_string()))?;
+    let unsigned = UnsignedTransaction {
+        raw: format!("0x{}", hex::encode(tr.to_rlp(Some(chain_id)))),
+        hash: format!("0x{}", hex::encode(tr.hash(chain_id))),
+    };
+    log::debug!("Unsigned transaction built from: {}", addr);
+
+    Ok(unsigned)
+}
+
+/// Decode hex data, rejecting odd length instead of padding,
+/// which would shift every ABI word
+fn decode_hex(value: &str) -> Result<Vec<u8>, Error> {
+    let value = util::trim_hex(value);
+    if value.len() % 2 != 0 {
+        return Err(Error::InvalidDataFormat(format!(
+            "Odd length of hex data: 0x{}",
+            value
+        )));
+    }
+
+    Ok(hex::decode(value)?)
+}
+
+/// Contracts storage of the chain given by request params
+fn chain_contracts<'a>(
+    storage_ctrl: &'a StorageController,
+    additional: &dyn CommonChainParams,
+) -> Result<&'a dyn ContractStorage, Error> {
+    let (chain, _chain_id) = extract_chain_params(additional, storage_ctrl.chains())?;
+    Ok(storage_ctrl.get_contracts(&chain)?)
+}
+
+/// Load contract ABI, given inline or by address of a contract from storage.
+/// Chain params are required only to find a stored contract.
+///
+/// # Arguments:
+///
+/// * spec - ABI array, `{"abi": [...]}`, contract address or `{"address": "0x..."}`
+/// * storage_ctrl - controller to find stored contract
+/// * additional - chain params
+///
+fn load_contract(
+    spec: &Value,
+    storage_ctrl: &StorageController,
+    additional: &dyn CommonChainParams,
+) -> Result<Option<Contract>, Error> {
+    let abi = match spec {
+        Value::Array(_) => spec.clone(),
+        Value::Object(obj) if obj.contains_key("abi") => obj["abi"].clone(),
+        Value::String(_) | Value::Object(_) => {
+            let addr = spec
+                .as_str()
+                .or_else(|| spec.get("address").and_then(Value::as_str))
+                .ok_or_else(|| Error::InvalidDataFormat("Missing contract address".to_string()))?;
+            let addr = Address::from_str(addr)?;
+            let contract = chain_contracts(storage_ctrl, additional)?
+                .get(&addr)
+                .ok_or_else(|| Error::InvalidDataFormat(format!("Unknown contract: {}", addr)))?;
+            contract.get("abi").cloned().ok_or_else(|| {
+                Error::InvalidDataFormat(format!("Missing ABI for contract: {}", addr))
+            })?
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(Contract::try_from(&serde_json::to_vec(&abi)?)?))
+}
+
+/// Load ABIs of all stored contracts of the chain, skipping invalid ones
+fn stored_contracts(storage: &dyn ContractStorage) -> Vec<(Option<String>, Contract)> {
+    storage
+        .list()
+        .iter()
+        .filter_map(|c| {
+            let abi = serde_json::to_vec(c.get("abi")?).ok()?;
+            let addr = c.get("address").and_then(Value::as_str).map(String::from);
+            Some((addr, Contract::try_from(&abi).ok()?))
+        })
+        .collect()
+}
+
+/// Label of a known address: name of stored contract or account of the chain
+fn address_label(storage_ctrl: &StorageController, chain: &str, addr: &Address) -> Option<String> {
+    let contract_name = storage_ctrl
+        .get_contracts(chain)
+        .ok()?
+        .get(addr)
+        .and_then(|c| c.get("name").and_then(Value::as_str).map(String::from));
+
+    let name = match contract_name {
+        Some(name) => name,
+        None => {
+            let (_, storage) = storage_ctrl.resolve_keystore(chain, addr).ok()?;
+            match storage.search_by_address(addr) {
+                Ok((info, _)) => info.name,
+                Err(_) => storage.search_watch_only(addr).ok()?.name,
+            }
+        }
+    };
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Wrap token contract call into transaction, signed if passphrase is given
+#[allow(clippy::too_many_arguments)]
+fn token_transaction(
+    storage_ctrl: &StorageController,
+    additional: &SignTxAdditional,
+    method: &str,
+    token: &Address,
+    from: &Address,
+    data: &[u8],
+    (gas, gas_price, nonce): (String, String, String),
+    passphrase: Option<String>,
+    meta: &Meta,
+) -> Result<TokenTransfer, Error> {
+    let (chain, chain_id) = extract_chain_params(additional, storage_ctrl.chains())?;
+    let transaction = SignTxTransaction {
+        from: from.to_string(),
+        to: Some(token.to_string()),
+        gas,
+        gas_price,
+        value: "0x0".to_string(),
+        data: format!("0x{}", hex::encode(data)),
+        nonce,
+    };
+
+    let signed = match passphrase {
+        Some(ref passphrase) => {
+            let raw = sign_raw_transaction(
+                storage_ctrl,
+                transaction.clone(),
+                passphrase,
+                &chain,
+                chain_id,
+                method,
+                &additional.caller,
+                meta,
+            )?;
+            Some(format!("0x{}", hex::encode(raw)))
+        }
+        None => None,
+    };
+
+    Ok(TokenTransfer {
+        transaction,
+        signed,
+    })
+}
+
+pub fn build_token_transfer(
+    params: Either<(TokenTransferParams,), (TokenTransferParams, SignTxAdditional)>,
+    storage_ctrl: &StorageController,
+    meta: &Meta,
+) -> Result<TokenTransfer, Error> {
+    let (transfer, additional) = params.into_full();
+    let token = Address::from_str(&transfer.token)?;
+    let from = Address::from_str(&transfer.from)?;
+    let to = Address::from_str(&transfer.to)?;
+    let amount = util::parse_units(
+        &transfer.amount,
+        transfer.decimals.unwrap_or_default() as usize,
+    )
+    .ok_or_else(|| Error::InvalidDataFormat(format!("Invalid amount: {}", transfer.amount)))?;
+
+    let data = contract::erc20_transfer(&to, &amount)?;
+    let res = token_transaction(
+        storage_ctrl,
+        &additional,
+        "signer_buildTokenTransfer",
+        &token,
+        &from,
+        &data,
+        (transfer.gas, transfer.gas_price, transfer.nonce),
+        transfer.passphrase,
+        meta,
+    )?;
+    log::debug!("Token transfer built: {} of {} to {}", amount, token, to);
+
+    Ok(res)
+}
+
+pub fn build_nft_transfer(
+    params: Either<(NftTransferParams,), (NftTransferParams, SignTxAdditional)>,
+    storage_ctrl: &StorageController,
+    meta: &Meta,
+) -> Result<TokenTransfer, Error> {
+    let (transfer, additional) = params.into_full();
+    let token = Address::from_str(&transfer.token)?;
+    let from = Address::from_str(&transfer.from)?;
+    let to = Address::from_str(&transfer.to)?;
+    let token_id = util::parse_units(&transfer.token_id, 0).ok_or_else(|| {
+        Error::InvalidDataFormat(format!("Invalid token id: {}", transfer.token_id))
+    })?;
+
+    let data = contract::erc721_transfer(&from, &to, &token_id)?;
+    let res = token_transaction(
+        storage_ctrl,
+        &additional,
+        "signer_buildNftTransfer",
+        &token,
+        &from,
+        &data,
+        (transfer.gas, transfer.gas_price, transfer.nonce),
+        transfer.passphrase,
+        meta,
+    )?;
+    log::debug!("NFT transfer built: #{} of {} to {}", token_id, token, to);
+
+    Ok(res)
+}
+
+/// Parse 32 bytes hex param
+fn parse_bytes32(name: &str, value: &str) -> Result<[u8; 32], Error> {
+    let bytes = hex::decode(util::trim_hex(value))?;
+    if bytes.len() != 32 {
+        return Err(Error::InvalidDataFormat(format!(
+            "Expected 32 bytes {}, got {}",
+            name,
+            bytes.len()
+        )));
     }
+
+    Ok(util::to_arr(&bytes))
+}
+
+pub fn compute_create2_address(params: (Create2Params,)) -> Result<Create2Address, Error> {
+    let (create2,) = params;
+    let deployer = Address::from_str(&create2.deployer)?;
+    let salt = parse_bytes32("salt", &create2.salt)?;
+
+    let init_code = match create2.init_code {
+        Some(ref code) => Some(decode_hex(code)?),
+        None => None,
+    };
+    let init_code_hash = match (&init_code, create2.init_code_hash) {
+        (Some(code), None) => util::keccak256(code),
+        (None, Some(ref hash)) => parse_bytes32("init code hash", hash)?,
+        _ => {
+            return Err(Error::InvalidDataFormat(
+                "Expected either init code or its hash".to_string(),
+            ))
+        }
+    };
+
+    let deploy_call = match init_code {
+        Some(ref code) if create2.encode_deploy => Some(format!(
+            "0x{}",
+            hex::encode(contract::factory_deploy(&salt, code)?)
+        )),
+        None if create2.encode_deploy => {
+            return Err(Error::InvalidDataFormat(
+                "Init code is required to encode deploy call".to_string(),
+            ))
+        }
+        _ => None,
+    };
+
+    let address = deployer.create2_address(&salt, &init_code_hash);
+    log::debug!("CREATE2 address computed: {}", address);
+
+    Ok(Create2Address {
+        address: address.to_string(),
+        init_code_hash: format!("0x{}", hex::encode(init_code_hash)),
+        deploy_call,
+    })
 }
 
 pub fn encode_function_call(
-    params: Either<(Value,), (Value, FunctionParams)>,
+    params: FunctionCallParams<(Value, FunctionParams, CommonAdditional)>,
+    storage_ctrl: &StorageController,
 ) -> Result<String, Error> {
-    let (_, inputs) = params.into_full();
+    let (spec, inputs, additional) = params.into_full();
+    let encoded = match inputs.name {
+        Some(ref name) => {
+            let data = match load_contract(&spec, storage_ctrl, &additional)? {
+                Some(contract) => contract.serialize_call(name, inputs.values)?,
+                None => Contract::serialize_signature_call(name, &inputs.types, inputs.values)?,
+            };
+            hex::encode(data)
+        }
+        // ABI-encoded params only, without function selector
+        None => Contract::serialize_params(&inputs.types, inputs.values)?,
+    };
+    log::debug!("Function call encoded: {:?}", inputs.name);
+
+    Ok(format!("0x{}", encoded))
+}
+
+pub fn decode_function_call(
+    params: DecodeCallParams<(String, Value, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<DecodedFunctionCall, Error> {
+    let (data, spec, additional) = params.into_full();
+    let data = decode_hex(&data)?;
+
+    let res = match load_contract(&spec, storage_ctrl, &additional)? {
+        Some(contract) => DecodedFunctionCall {
+            contract: None,
+            call: contract.decode_call(&data)?,
+        },
+        // search all stored contracts of the chain
+        None => stored_contracts(chain_contracts(storage_ctrl, &additional)?)
+            .into_iter()
+            .find_map(|(addr, contract)| {
+                Some(DecodedFunctionCall {
+                    contract: addr,
+                    call: contract.decode_call(&data).ok()?,
+                })
+            })
+            .ok_or_else(|| {
+                Error::InvalidDataFormat("No stored contract matches call data".to_string())
+            })?,
+    };
+    log::debug!("Function call decoded: {}", res.call.signature);
 
-    Contract::serialize_params(&inputs.types, inputs.values).map_err(From::from)
+    Ok(res)
+}
+
+pub fn decode_function_result(
+    params: DecodeResultParams<(String, Value, String, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<DecodedCall, Error> {
+    let (data, spec, name, additional) = params.into_full();
+    let data = decode_hex(&data)?;
+
+    let contract = load_contract(&spec, storage_ctrl, &additional)?
+        .ok_or_else(|| Error::InvalidDataFormat("Missing contract ABI".to_string()))?;
+    let res = contract.decode_output(&name, &data)?;
+    log::debug!("Function result decoded: {}", res.signature);
+
+    Ok(res)
+}
+
+pub fn decode_logs(
+    params: Either<(Vec<LogParams>,), (Vec<LogParams>, DecodeLogsAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<Option<DecodedLog>>, Error> {
+    let (logs, additional) = params.into_full();
+
+    let contracts = match additional.contract {
+        Some(ref spec) => load_contract(spec, storage_ctrl, &additional)?
+            .map(|c| vec![(None, c)])
+            .unwrap_or_default(),
+        None => stored_contracts(chain_contracts(storage_ctrl, &additional)?),
+    };
+
+    let mut res = Vec::with_capacity(logs.len());
+    for entry in logs {
+        let topics = entry
+            .topics
+            .iter()
+            .map(|t| decode_hex(t))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = decode_hex(&entry.data)?;
+        let emitter = match entry.address {
+            Some(ref a) => Some(Address::from_str(a)?),
+            None => None,
+        };
+
+        // ABI of the contract emitted the log goes first
+        let decoded = contracts
+            .iter()
+            .filter(|(addr, _)| {
+                emitter.is_some()
+                    && addr.as_ref().and_then(|a| Address::from_str(a).ok()) == emitter
+            })
+            .chain(contracts.iter())
+            .find_map(|(addr, contract)| {
+                Some(DecodedLog {
+                    contract: addr.clone(),
+                    event: contract.decode_log(&topics, &data).ok()?,
+                })
+            });
+        res.push(decoded);
+    }
+    log::debug!(
+        "Logs decoded: {} of {}",
+        res.iter().filter(|l| l.is_some()).count(),
+        res.len()
+    );
+
+    Ok(res)
+}
+
+pub fn preview_transaction(
+    params: Either<(SignTxTransaction,), (SignTxTransaction, SignTxAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<TransactionPreview, Error> {
+    let (transaction, additional) = params.into_full();
+    let (chain, chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let from = Address::from_str(&transaction.from)?;
+    let tr = transaction
+        .try_into()
+        .map_err(|err| Error::InvalidDataFormat(err.to_string()))?;
+
+    // intrinsic gas, with EIP-2028 calldata costs
+    let mut intrinsic = if tr.to.is_some() { 21_000 } else { 53_000 };
+    intrinsic += tr
+        .data
+        .iter()
+        .map(|b| if *b == 0 { 4 } else { 16 })
+        .sum::<u64>();
+    let gas_price = ethabi::Uint::from_big_endian(&tr.gas_price);
+    let fee = |gas: u64| match gas_price.checked_mul(gas.into()) {
+        Some(fee) => Ok(util::format_units(&fee.to_string(), 18)),
+        None => Err(Error::InvalidDataFormat(
+            "Transaction fee exceeds 256 bits".to_string(),
+        )),
+    };
+    let fee = FeeBounds {
+        min: fee(intrinsic.min(tr.gas_limit))?,
+        max: fee(tr.gas_limit)?,
+    };
+
+    let call = match tr.to {
+        Some(ref to) if !tr.data.is_empty() => storage_ctrl
+            .get_contracts(&chain)?
+            .get(to)
+            .and_then(|c| serde_json::to_vec(c.get("abi")?).ok())
+            .and_then(|abi| Contract::try_from(&abi).ok())
+            .and_then(|contract| match contract.decode_call(&tr.data) {
+                Ok(call) => Some(call),
+                Err(e) => {
+                    log::debug!("Transaction data isn't decoded for preview: {}", e);
+                    None
+                }
+            }),
+        _ => None,
+    };
+
+    let preview = TransactionPreview {
+        from_label: address_label(storage_ctrl, &chain, &from),
+        to_label: tr
+            .to
+            .and_then(|to| address_label(storage_ctrl, &chain, &to)),
+        chain,
+        chain_id,
+        from: from.to_string(),
+        to: tr.to.map(|to| to.to_string()),
+        value: util::format_units(&ethabi::Uint::from_big_endian(&tr.value).to_string(), 18),
+        nonce: tr.nonce,
+        gas_limit: tr.gas_limit,
+        gas_price: util::format_units(&gas_price.to_string(), 9),
+        fee,
+        data: format!("0x{}", hex::encode(&tr.data)),
+        call,
+    };
+    log::debug!("Transaction preview built: {:?}", preview);
+
+    Ok(preview)
 }
 
 pub fn list_contracts(
     params: Either<(), (CommonAdditional,)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<Vec<serde_json::Value>, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (additional,) = params.into_right();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let storage = storage_ctrl.get_contracts(&chain)?;
 
     Ok(storage.list())
@@ -325,25 +1240,94 @@ pub fn list_contracts(
 
 pub fn import_contract(
     params: Either<(Value,), (Value, CommonAdditional)>,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<(), Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (raw, additional) = params.into_full();
-    let (chain, _chain_id) = extract_chain_params(&additional)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let storage = storage_ctrl.get_contracts(&chain)?;
 
     storage.add(&raw)?;
     Ok(())
 }
 
-//pub fn export_contract(
-//    params: Either<(Value,), (Value, FunctionParams)>,
-//    storage: &Arc<Mutex<StorageController>>,
-//) -> Result<Value, Error> {
-//    let storage_ctrl = storage.lock().unwrap();
-//    let (_, inputs) = params.into_full();
-//    let storage = storage_ctrl.get_contracts(&additional.chain)?;
-//}
+/// Find stored contract by address or name
+fn find_contract(storage: &dyn ContractStorage, query: &ContractQuery) -> Result<Value, Error> {
+    if let Some(ref addr) = query.address {
+        let addr = Address::from_str(addr)?;
+        return storage
+            .get(&addr)
+            .ok_or_else(|| contract::Error::NotFound(addr.to_string()).into());
+    }
+
+    let name = query
+        .name
+        .as_ref()
+        .ok_or_else(|| Error::InvalidDataFormat("Missing contract address or name".to_string()))?;
+    let mut found = storage.find_by_name(name);
+    match found.len() {
+        0 => Err(contract::Error::NotFound(name.to_string()).into()),
+        1 => Ok(found.remove(0)),
+        n => Err(Error::InvalidDataFormat(format!(
+            "{} contracts found with name: {}, use address instead",
+            n, name
+        ))),
+    }
+}
+
+/// Address of stored contract
+fn stored_address(contract: &Value) -> Result<Address, Error> {
+    let addr = contract
+        .get("address")
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::InvalidDataFormat("Missing contract address".to_string()))?;
+
+    Ok(Address::from_str(addr)?)
+}
+
+pub fn export_contract(
+    params: Either<(ContractQuery,), (ContractQuery, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<Value, Error> {
+    let (query, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_contracts(&chain)?;
+
+    let contract = find_contract(storage, &query)?;
+    log::debug!("Contract exported: {:?}", contract.get("address"));
+
+    Ok(contract)
+}
+
+pub fn update_contract(
+    params: UpdateContractParams<(ContractQuery, Value, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (query, changes, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_contracts(&chain)?;
+    let addr = stored_address(&find_contract(storage, &query)?)?;
+
+    storage.update(&addr, &changes)?;
+    log::debug!("Contract updated: {}", addr);
+
+    Ok(true)
+}
+
+pub fn delete_contract(
+    params: Either<(ContractQuery,), (ContractQuery, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<bool, Error> {
+    let (query, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_contracts(&chain)?;
+
+    let addr = stored_address(&find_contract(storage, &query)?)?;
+
+    storage.delete(&addr)?;
+    log::debug!("Contract deleted: {}", addr);
+
+    Ok(true)
+}
 
 pub fn generate_mnemonic() -> Result<String, Error> {
     let entropy = gen_entropy(ENTROPY_BYTE_LENGTH)?;
@@ -355,18 +1339,30 @@ pub fn generate_mnemonic() -> Result<String, Error> {
 pub fn import_mnemonic(
     params: Either<(NewMnemonicAccount,), (NewMnemonicAccount, CommonAdditional)>,
     sec: &KdfDepthLevel,
-    storage: &Arc<Mutex<StorageController>>,
+    storage_ctrl: &StorageController,
 ) -> Result<String, Error> {
-    let storage_ctrl = storage.lock().unwrap();
     let (account, additional) = params.into_full();
-    let (chain, _) = extract_chain_params(&additional)?;
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
     let storage = storage_ctrl.get_keystore(&chain)?;
     if account.passphrase.is_empty() {
         return Err(Error::InvalidDataFormat("Empty passphrase".to_string()));
     }
 
     let mnemonic = Mnemonic::try_from(Language::English, &account.mnemonic)?;
-    let hd_path = HDPath::try_from(&account.hd_path)?;
+    let hd_path = match account.hd_path {
+        Some(ref path) => {
+            if account.account.is_some() || account.index.is_some() {
+                return Err(Error::InvalidDataFormat(
+                    "Either `hd_path` or `account`/`index` required, not both".to_string(),
+                ));
+            }
+            HDPath::try_from(path)?
+        }
+        None => storage_ctrl.chain(&chain)?.default_path(
+            account.account.unwrap_or_default(),
+            account.index.unwrap_or_default(),
+        )?,
+    };
     let pk = hd_path::generate_key(&hd_path, &mnemonic.seed(""))?;
 
     let kdf = if cfg!(target_os = "windows") {
@@ -386,8 +1382,297 @@ pub fn import_mnemonic(
     )?;
 
     let addr = kf.address.to_string();
+    let lock = storage_ctrl.account_lock(&chain, &kf.address);
+    let _guard = lock.lock().unwrap();
+    check_not_linked(storage_ctrl, &chain, &kf.address)?;
+    check_not_watch_only(storage, &kf.address)?;
     storage.put(&kf)?;
     log::debug!("New mnemonic account generated: {}", kf.address);
 
     Ok(addr)
 }
+
+pub fn import_mnemonic_range(
+    params: Either<(MnemonicRangeAccounts,), (MnemonicRangeAccounts, CommonAdditional)>,
+    sec: &KdfDepthLevel,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<String>, Error> {
+    let (accounts, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_keystore(&chain)?;
+    if accounts.passphrase.is_empty() {
+        return Err(Error::InvalidDataFormat("Empty passphrase".to_string()));
+    }
+    if accounts.count > MAX_IMPORT_COUNT {
+        return Err(Error::InvalidDataFormat(format!(
+            "Too many accounts requested: {}, max: {}",
+            accounts.count, MAX_IMPORT_COUNT
+        )));
+    }
+
+    let mnemonic = Mnemonic::try_from(Language::English, &accounts.mnemonic)?;
+    let base_path = match accounts.base_path {
+        Some(ref path) => HDPath::try_from(path)?,
+        None => HDPath::try_from(&storage_ctrl.chain(&chain)?.hd_path)?,
+    };
+    let keys = hd_path::generate_keys(
+        &base_path,
+        &mnemonic.seed(""),
+        accounts.start,
+        accounts.count,
+    )?;
+
+    let kdf = if cfg!(target_os = "windows") {
+        Kdf::from_str(PBKDF2_KDF_NAME)?
+    } else {
+        Kdf::from(*sec)
+    };
+
+    for (_, pk) in &keys {
+        let addr = pk.to_address()?;
+        check_not_linked(storage_ctrl, &chain, &addr)?;
+        check_not_watch_only(storage, &addr)?;
+    }
+
+    let mut rng = util::os_random();
+    let mut created = Vec::with_capacity(keys.len());
+    for (index, (path, pk)) in (accounts.start..).zip(keys.into_iter()) {
+        let addr = pk.to_address()?;
+        if storage.search_by_address(&addr).is_ok() {
+            log::debug!("Account already exists, skipped: {} ({})", addr, path);
+            continue;
+        }
+
+        let name = accounts
+            .name_template
+            .replace(NAME_TEMPLATE_INDEX, &index.to_string());
+        let kf = KeyFile::new_custom(
+            pk,
+            &accounts.passphrase,
+            kdf,
+            &mut rng,
+            Some(name),
+            Some(accounts.description.clone()),
+        )?;
+
+        let lock = storage_ctrl.account_lock(&chain, &addr);
+        let _guard = lock.lock().unwrap();
+        if storage.search_by_address(&addr).is_ok() {
+            log::debug!("Account was imported concurrently, skipped: {}", addr);
+            continue;
+        }
+        check_not_watch_only(storage, &addr)?;
+        storage.put(&kf)?;
+        created.push(kf.address.to_string());
+    }
+    log::debug!(
+        "Mnemonic accounts imported from: {}\n\t{:?}",
+        base_path,
+        created
+    );
+
+    Ok(created)
+}
+
+pub fn derive_addresses(params: (String, String, u32, u32)) -> Result<Vec<DerivedAddress>, Error> {
+    let (mnemonic, base_path, start, count) = params;
+    if count > MAX_DERIVE_COUNT {
+        return Err(Error::InvalidDataFormat(format!(
+            "Too many addresses requested: {}, max: {}",
+            count, MAX_DERIVE_COUNT
+        )));
+    }
+
+    let mnemonic = Mnemonic::try_from(Language::English, &mnemonic)?;
+    let hd_path = HDPath::try_from(&base_path)?;
+    let keys = hd_path::generate_keys(&hd_path, &mnemonic.seed(""), start, count)?;
+
+    let res = keys
+        .into_iter()
+        .map(|(path, pk)| {
+            Ok(DerivedAddress {
+                address: pk.to_address()?.to_string(),
+                hd_path: path.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    log::debug!("Derived {} addresses from: {}", res.len(), base_path);
+
+    Ok(res)
+}
+
+pub fn import_seed(
+    params: Either<(ImportSeedAccount,), (ImportSeedAccount, CommonAdditional)>,
+    sec: &KdfDepthLevel,
+    storage_ctrl: &StorageController,
+) -> Result<String, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_seeds(&chain)?;
+    if account.passphrase.is_empty() {
+        return Err(Error::InvalidDataFormat("Empty passphrase".to_string()));
+    }
+
+    let mnemonic = Mnemonic::try_from(Language::English, &account.mnemonic)?;
+    let kdf = if cfg!(target_os = "windows") {
+        Kdf::from_str(PBKDF2_KDF_NAME)?
+    } else {
+        Kdf::from(*sec)
+    };
+
+    let mut rng = util::os_random();
+    let mut crypto = CoreCrypto {
+        kdf_params: KdfParams {
+            kdf,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    crypto.encrypt_custom(&mnemonic.seed(""), &account.passphrase, &mut rng);
+
+    let seed = SeedAccount {
+        id: Uuid::from_bytes(rng.gen()),
+        name: account.name,
+        description: account.description,
+        crypto,
+    };
+    storage.add(&seed)?;
+    log::debug!("HD wallet seed stored: {}", seed.id);
+
+    Ok(seed.id.to_string())
+}
+
+pub fn list_seeds(
+    params: Either<(), (CommonAdditional,)>,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<SeedInfo>, Error> {
+    let (additional,) = params.into_right();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_seeds(&chain)?;
+
+    let res = storage
+        .list()
+        .into_iter()
+        .map(|seed| SeedInfo {
+            id: seed.id.to_string(),
+            name: seed.name,
+            description: seed.description,
+        })
+        .collect();
+
+    Ok(res)
+}
+
+pub fn export_xpub(
+    params: Either<(String, String), (SelectedSeed, String, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<String, Error> {
+    let (seed, path) = match params {
+        Either::Left((mnemonic, path)) => {
+            let mnemonic = Mnemonic::try_from(Language::English, &mnemonic)?;
+            (mnemonic.seed(""), path)
+        }
+        Either::Right((selected, path, additional)) => {
+            let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+            let stored = storage_ctrl.get_seeds(&chain)?.get(&selected.seed_id)?;
+            let seed = stored
+                .crypto
+                .decrypt(&selected.passphrase)
+                .map_err(|_| Error::InvalidDataFormat("Invalid passphrase".to_string()))?;
+            (seed, path)
+        }
+    };
+    let hd_path = HDPath::try_from(&path)?;
+    let xpub = hd_path::generate_xpub(&hd_path, &seed)?;
+    log::debug!("Xpub exported for: {}", hd_path);
+
+    Ok(xpub.to_string())
+}
+
+pub fn import_xpub(
+    params: Either<(ImportXpubAccount,), (ImportXpubAccount, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<String, Error> {
+    let (account, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_xpubs(&chain)?;
+
+    let xpub = hd_path::parse_xpub(&account.xpub)?.to_string();
+    storage.add(&XpubAccount {
+        xpub: xpub.clone(),
+        name: account.name,
+        description: account.description,
+    })?;
+    log::debug!("Watch-only xpub imported: {}", xpub);
+
+    Ok(xpub)
+}
+
+pub fn list_xpubs(
+    params: Either<(), (CommonAdditional,)>,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<XpubAccount>, Error> {
+    let (additional,) = params.into_right();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_xpubs(&chain)?;
+
+    Ok(storage.list())
+}
+
+pub fn list_xpub_addresses(
+    params: Either<(XpubAddressesParams,), (XpubAddressesParams, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<XpubAddress>, Error> {
+    let (inputs, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+    let storage = storage_ctrl.get_xpubs(&chain)?;
+    if inputs.count > MAX_DERIVE_COUNT {
+        return Err(Error::InvalidDataFormat(format!(
+            "Too many addresses requested: {}, max: {}",
+            inputs.count, MAX_DERIVE_COUNT
+        )));
+    }
+
+    let xpub = hd_path::parse_xpub(&inputs.xpub)?;
+    storage.get(&xpub.to_string())?;
+
+    let res = hd_path::derive_xpub_addresses(&xpub, inputs.start, inputs.count)?
+        .into_iter()
+        .map(|(index, addr)| XpubAddress {
+            address: addr.to_string(),
+            index,
+        })
+        .collect();
+
+    Ok(res)
+}
+
+pub fn list_chains(storage_ctrl: &StorageController) -> Result<Vec<ChainInfo>, Error> {
+    Ok(storage_ctrl.chains().list().to_vec())
+}
+
+pub fn list_audit_log(
+    params: Either<(AuditFilter,), (AuditFilter, CommonAdditional)>,
+    storage_ctrl: &StorageController,
+) -> Result<Vec<AuditEntry>, Error> {
+    let (filter, additional) = params.into_full();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+
+    let entries = storage_ctrl.get_audit_log(&chain)?.entries(&filter)?;
+    log::debug!("Audit log entries listed: {}", entries.len());
+
+    Ok(entries)
+}
+
+pub fn verify_audit_log(
+    params: Either<(), (CommonAdditional,)>,
+    storage_ctrl: &StorageController,
+) -> Result<AuditVerification, Error> {
+    let (additional,) = params.into_right();
+    let (chain, _chain_id) = extract_chain_params(&additional, storage_ctrl.chains())?;
+
+    let res = storage_ctrl.get_audit_log(&chain)?.verify()?;
+    log::debug!("Audit log verified for {}: {:?}", chain, res);
+
+    Ok(res)
+}
@@ -45,6 +45,7 @@ from_err!(num::ParseIntError);
 from_err!(hex::FromHexError);
 from_err!(crate::Error);
 from_err!(crate::mnemonic::Error);
+from_err!(crate::chains::Error);
 from_err!(url::ParseError);
 from_err!(serde_json::Error);
 from_err!(hyper::error::Error);
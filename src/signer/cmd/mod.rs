@@ -12,13 +12,17 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::chains::ChainRegistry;
 use crate::keystore::KdfDepthLevel;
-use crate::storage::{default_path, StorageController, StorageType};
+use crate::storage::{default_path, AuditFilter, StorageController, StorageType};
 
 type ExecResult = Result<(), Error>;
 
 const DEFAULT_CHAIN_NAME: &str = "mainnet";
 
+/// Chains config looked up inside base dir, if not specified explicitly
+const CHAINS_CONFIG_NAME: &str = "chains.json";
+
 /// Create new command executor
 pub fn execute(matches: &ArgMatches) -> ExecResult {
     let env = EnvVars::parse();
@@ -42,18 +46,99 @@ pub fn execute(matches: &ArgMatches) -> ExecResult {
         StorageType::RocksDB
     };
 
-    let storage_ctrl = StorageController::new(base_path, storage_type)?;
+    let chains_config = match matches.value_of("chains") {
+        Some(p) => Some(PathBuf::from(p)),
+        None => Some(base_path.join(CHAINS_CONFIG_NAME)).filter(|p| p.exists()),
+    };
+    let chains = match chains_config {
+        Some(p) => {
+            log::info!("Loading chains config from: {}", p.display());
+            ChainRegistry::load(p)?
+        }
+        None => ChainRegistry::default(),
+    };
+
+    let storage_ctrl = StorageController::new(base_path, storage_type, chains)?;
+
+    if let Some(sub) = matches.subcommand_matches("migrate") {
+        return migrate_cmd(sub, &storage_ctrl);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("audit") {
+        return audit_cmd(sub, chain, &storage_ctrl);
+    }
 
     log::info!("Starting Jade Signer - v{}", crate::version());
     let host = matches.value_of("host").unwrap_or_default();
     let port = matches.value_of("port").unwrap_or_default();
     let addr = format!("{}:{}", host, port).parse::<SocketAddr>()?;
     let sec_lvl = get_security_lvl(matches)?;
+    let threads = matches
+        .value_of("threads")
+        .unwrap_or_default()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| Error::ExecError("Invalid number of RPC threads".to_string()))?;
+    let admin_addr = match matches.value_of("admin-port") {
+        Some(port) => Some(format!("{}:{}", host, port).parse::<SocketAddr>()?),
+        None => None,
+    };
 
     log::info!("Chain set to '{}'", chain);
     log::info!("Security level set to '{}'", sec_lvl);
+    log::info!("RPC threads: {}", threads);
+
+    crate::rpc::start(&addr, storage_ctrl, Some(sec_lvl), threads, admin_addr);
+
+    Ok(())
+}
+
+/// Copy keyfiles from current storage into storage of another type
+fn migrate_cmd(matches: &ArgMatches, storage_ctrl: &StorageController) -> ExecResult {
+    let target = StorageType::from_str(matches.value_of("to").unwrap_or_default())?;
+
+    for report in storage_ctrl.migrate(target)? {
+        log::info!(
+            "Chain '{}': {} keyfiles, {} watch-only accounts migrated, {} skipped",
+            report.chain,
+            report.keyfiles,
+            report.watch_only,
+            report.skipped
+        );
+    }
+
+    Ok(())
+}
+
+/// Print audit log entries of a chain or verify its integrity
+fn audit_cmd(matches: &ArgMatches, chain: &str, storage_ctrl: &StorageController) -> ExecResult {
+    let chain = &storage_ctrl.chain(chain)?.name;
+    let log = storage_ctrl.get_audit_log(chain)?;
+
+    if matches.is_present("verify") {
+        let res = log.verify()?;
+        println!("{}", serde_json::to_string_pretty(&res)?);
+        if !res.valid {
+            return Err(Error::ExecError(format!(
+                "Audit log for chain '{}' is broken at entry {:?}",
+                chain, res.broken_at
+            )));
+        }
+        return Ok(());
+    }
 
-    crate::rpc::start(&addr, storage_ctrl, Some(sec_lvl));
+    let filter = AuditFilter {
+        account: matches.value_of("account").map(str::to_string),
+        method: None,
+        limit: match matches.value_of("limit") {
+            Some(limit) => Some(limit.parse()?),
+            None => None,
+        },
+    };
+    for entry in log.entries(&filter)? {
+        println!("{}", serde_json::to_string(&entry)?);
+    }
 
     Ok(())
 }
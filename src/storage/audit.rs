@@ -0,0 +1,492 @@
+//! # Append-only, hash-chained audit log of signing operations
+//!
+//! Each entry includes hash of the previous one, so any modification,
+//! removal or reordering of entries breaks the chain and is detected by `verify`.
+//!
+//! Caller identity is declared by the client itself and is not authenticated,
+//! it only helps to tell apart cooperating clients. Peer address is taken
+//! from the transport and is recorded next to it.
+//!
+
+use super::KeystoreError;
+use crate::util::keccak256;
+use chrono::Utc;
+use hex;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Hash of the (absent) entry preceding the first one
+const GENESIS_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Signing operation to be recorded
+#[derive(Debug, Clone, Default)]
+pub struct AuditRecord {
+    /// RPC method name
+    pub method: String,
+
+    /// Signing account address
+    pub account: String,
+
+    /// Chain name
+    pub chain: String,
+
+    /// Hash of signed payload
+    pub payload_hash: String,
+
+    /// Caller identity, self-declared by client and not authenticated
+    pub caller: String,
+
+    /// Network address of peer that sent the request, if known
+    pub peer: Option<String>,
+}
+
+/// Audit log entry
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Sequence number, starting from 0
+    pub index: u64,
+
+    /// Time of operation, RFC 3339
+    pub timestamp: String,
+
+    /// RPC method name
+    pub method: String,
+
+    /// Signing account address
+    pub account: String,
+
+    /// Chain name
+    pub chain: String,
+
+    /// Hash of signed payload
+    pub payload_hash: String,
+
+    /// Caller identity, self-declared by client and not authenticated
+    pub caller: String,
+
+    /// Network address of peer that sent the request, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer: Option<String>,
+
+    /// Hash of previous entry
+    pub prev_hash: String,
+
+    /// Hash of this entry
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Calculate hash of entry content, including hash of previous entry.
+    /// Peer is hashed only if present, so entries recorded without it still verify.
+    fn calculate_hash(&self) -> String {
+        let mut content = format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}",
+            self.index,
+            self.timestamp,
+            self.method,
+            self.account,
+            self.chain,
+            self.payload_hash,
+            self.caller,
+            self.prev_hash
+        );
+        if let Some(ref peer) = self.peer {
+            content.push_str(&format!("|{}", peer));
+        }
+
+        format!("0x{}", hex::encode(keccak256(content.as_bytes())))
+    }
+}
+
+/// Filter for audit log query
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only entries for specified account
+    #[serde(default)]
+    pub account: Option<String>,
+
+    /// Only entries for specified method
+    #[serde(default)]
+    pub method: Option<String>,
+
+    /// Only last `limit` matching entries
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.account
+            .as_ref()
+            .map_or(true, |a| a.eq_ignore_ascii_case(&entry.account))
+            && self.method.as_ref().map_or(true, |m| *m == entry.method)
+    }
+}
+
+/// Result of audit log verification
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditVerification {
+    /// Number of checked entries
+    pub entries: u64,
+
+    /// Whether whole chain of entries is intact
+    pub valid: bool,
+
+    /// Index of first broken entry
+    pub broken_at: Option<u64>,
+}
+
+#[derive(Debug)]
+enum Backend {
+    /// JSON lines file
+    File(PathBuf),
+
+    /// Nothing is persisted
+    Memory(Vec<AuditEntry>),
+}
+
+#[derive(Debug)]
+struct State {
+    backend: Backend,
+
+    /// Index and hash of last entry
+    last: Option<(u64, String)>,
+}
+
+/// Audit log for a single chain
+#[derive(Debug)]
+pub struct AuditLog {
+    state: Mutex<State>,
+}
+
+impl AuditLog {
+    /// Open audit log stored in a file, created on first append.
+    /// Trailing line torn by interrupted append is truncated,
+    /// other malformed lines are reported by `verify`.
+    ///
+    /// # Arguments:
+    ///
+    /// * path - path to log file
+    ///
+    pub fn new(path: PathBuf) -> Result<AuditLog, KeystoreError> {
+        repair_tail(&path)?;
+        let last = read_lines(&path)?
+            .into_iter()
+            .flatten()
+            .last()
+            .map(|e| (e.index, e.hash.clone()));
+
+        Ok(AuditLog {
+            state: Mutex::new(State {
+                backend: Backend::File(path),
+                last,
+            }),
+        })
+    }
+
+    /// Create audit log kept in memory only
+    pub fn in_memory() -> AuditLog {
+        AuditLog {
+            state: Mutex::new(State {
+                backend: Backend::Memory(vec![]),
+                last: None,
+            }),
+        }
+    }
+
+    /// Append new entry to the log
+    ///
+    /// # Arguments:
+    ///
+    /// * record - signing operation
+    ///
+    pub fn append(&self, record: AuditRecord) -> Result<AuditEntry, KeystoreError> {
+        let mut state = self.state.lock().unwrap();
+        let (index, prev_hash) = match state.last {
+            Some((index, ref hash)) => (index + 1, hash.clone()),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+
+        let mut entry = AuditEntry {
+            index,
+            timestamp: Utc::now().to_rfc3339(),
+            method: record.method,
+            account: record.account,
+            chain: record.chain,
+            payload_hash: record.payload_hash,
+            caller: record.caller,
+            peer: record.peer,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.calculate_hash();
+
+        match state.backend {
+            Backend::File(ref path) => {
+                let mut f = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(f, "{}", serde_json::to_string(&entry)?)?;
+                f.sync_data()?;
+            }
+            Backend::Memory(ref mut entries) => entries.push(entry.clone()),
+        }
+        state.last = Some((entry.index, entry.hash.clone()));
+
+        Ok(entry)
+    }
+
+    /// List log entries matching filter
+    ///
+    /// # Arguments:
+    ///
+    /// * filter - query filter
+    ///
+    pub fn entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, KeystoreError> {
+        let mut res: Vec<AuditEntry> = self
+            .all()?
+            .into_iter()
+            .flatten()
+            .filter(|e| filter.matches(e))
+            .collect();
+
+        if let Some(limit) = filter.limit {
+            let skip = res.len().saturating_sub(limit);
+            res.drain(..skip);
+        }
+
+        Ok(res)
+    }
+
+    /// Check integrity of the whole chain of entries,
+    /// malformed line breaks the chain at its position
+    pub fn verify(&self) -> Result<AuditVerification, KeystoreError> {
+        let entries = self.all()?;
+        let mut prev_hash = GENESIS_HASH.to_string();
+
+        for (i, entry) in entries.iter().enumerate() {
+            match entry {
+                Some(entry)
+                    if entry.index == i as u64
+                        && entry.prev_hash == prev_hash
+                        && entry.hash == entry.calculate_hash() =>
+                {
+                    prev_hash = entry.hash.clone()
+                }
+                _ => {
+                    return Ok(AuditVerification {
+                        entries: entries.len() as u64,
+                        valid: false,
+                        broken_at: Some(i as u64),
+                    })
+                }
+            }
+        }
+
+        Ok(AuditVerification {
+            entries: entries.len() as u64,
+            valid: true,
+            broken_at: None,
+        })
+    }
+
+    fn all(&self) -> Result<Vec<Option<AuditEntry>>, KeystoreError> {
+        let state = self.state.lock().unwrap();
+        match state.backend {
+            Backend::File(ref path) => read_lines(path),
+            Backend::Memory(ref entries) => Ok(entries.iter().cloned().map(Some).collect()),
+        }
+    }
+}
+
+/// Read all lines of log file, `None` for malformed ones
+fn read_lines(path: &Path) -> Result<Vec<Option<AuditEntry>>, KeystoreError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut entries = vec![];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(&line).ok());
+    }
+
+    Ok(entries)
+}
+
+/// Fix last line of log file left without line break by interrupted append:
+/// complete entry gets its line break, torn one is truncated
+fn repair_tail(path: &Path) -> Result<(), KeystoreError> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read(path)?;
+    if content.is_empty() || content.ends_with(b"\n") {
+        return Ok(());
+    }
+
+    let start = content
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    let mut f = OpenOptions::new().append(true).open(path)?;
+    if serde_json::from_slice::<AuditEntry>(&content[start..]).is_ok() {
+        f.write_all(b"\n")?;
+    } else {
+        log::warn!("Truncated torn audit log entry in {:?}", path);
+        f.set_len(start as u64)?;
+    }
+    f.sync_data()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn record(account: &str) -> AuditRecord {
+        AuditRecord {
+            method: "signer_sign".to_string(),
+            account: account.to_string(),
+            chain: "etc".to_string(),
+            payload_hash: "0x01".to_string(),
+            caller: "test".to_string(),
+            peer: None,
+        }
+    }
+
+    #[test]
+    fn should_append_and_verify() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone()).unwrap();
+
+        let first = log.append(record("0x01")).unwrap();
+        let second = log.append(record("0x02")).unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+
+        let log = AuditLog::new(path).unwrap();
+        let third = log.append(record("0x01")).unwrap();
+        assert_eq!(third.index, 2);
+        assert_eq!(third.prev_hash, second.hash);
+
+        assert_eq!(
+            log.verify().unwrap(),
+            AuditVerification {
+                entries: 3,
+                valid: true,
+                broken_at: None,
+            }
+        );
+    }
+
+    #[test]
+    fn should_detect_tampering() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone()).unwrap();
+        log.append(record("0x01")).unwrap();
+        log.append(record("0x02")).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let tampered: Vec<String> = content
+            .lines()
+            .map(|l| l.replace(r#""account":"0x02""#, r#""account":"0x03""#))
+            .collect();
+        std::fs::write(&path, tampered.join("\n")).unwrap();
+
+        let res = log.verify().unwrap();
+        assert!(!res.valid);
+        assert_eq!(res.broken_at, Some(1));
+    }
+
+    #[test]
+    fn should_detect_stripped_peer() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone()).unwrap();
+        log.append(record("0x01")).unwrap();
+        log.append(AuditRecord {
+            peer: Some("127.0.0.1:50000".to_string()),
+            ..record("0x02")
+        })
+        .unwrap();
+        assert!(log.verify().unwrap().valid);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.lines().next().unwrap().contains("peer"));
+        std::fs::write(&path, content.replace(r#","peer":"127.0.0.1:50000""#, "")).unwrap();
+
+        let res = log.verify().unwrap();
+        assert!(!res.valid);
+        assert_eq!(res.broken_at, Some(1));
+    }
+
+    #[test]
+    fn should_truncate_torn_tail() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone()).unwrap();
+        let first = log.append(record("0x01")).unwrap();
+
+        let mut f = OpenOptions::new().append(true).open(&path).unwrap();
+        f.write_all(br#"{"index":1,"timestamp":"#).unwrap();
+
+        let log = AuditLog::new(path).unwrap();
+        let second = log.append(record("0x02")).unwrap();
+        assert_eq!(second.index, 1);
+        assert_eq!(second.prev_hash, first.hash);
+        assert!(log.verify().unwrap().valid);
+    }
+
+    #[test]
+    fn should_report_malformed_line() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone()).unwrap();
+        log.append(record("0x01")).unwrap();
+        log.append(record("0x02")).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines: Vec<&str> = content.lines().collect();
+        lines[0] = "garbage";
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let log = AuditLog::new(path).unwrap();
+        assert_eq!(log.entries(&AuditFilter::default()).unwrap().len(), 1);
+        assert_eq!(
+            log.verify().unwrap(),
+            AuditVerification {
+                entries: 2,
+                valid: false,
+                broken_at: Some(0),
+            }
+        );
+    }
+
+    #[test]
+    fn should_filter_entries() {
+        let log = AuditLog::in_memory();
+        log.append(record("0x01")).unwrap();
+        log.append(record("0x02")).unwrap();
+        log.append(record("0x01")).unwrap();
+
+        let filter = AuditFilter {
+            account: Some("0x01".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let entries = log.entries(&filter).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].index, 2);
+    }
+}
@@ -0,0 +1,119 @@
+//! # Contracts storage backed with `db`
+
+use super::ContractStorage;
+use crate::contract::Error;
+use crate::core::Address;
+use rocksdb::{IteratorMode, DB};
+use serde_json;
+use std::path::Path;
+
+/// Database backed storage for contracts
+/// Storage structure:
+///     key - contract `Address`
+///     value - contract json
+///
+pub struct DbContractStorage {
+    /// Database handler
+    db: DB,
+}
+
+impl DbContractStorage {
+    /// Create new database storage for contracts
+    /// Use specified directory as parent folder
+    ///
+    /// # Arguments:
+    ///
+    /// * dir - parent folder
+    ///
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<DbContractStorage, Error> {
+        let db = DB::open_default(dir).map_err(|e| Error::IO(e.to_string()))?;
+
+        Ok(DbContractStorage { db })
+    }
+}
+
+impl ContractStorage for DbContractStorage {
+    fn put(&self, contract: &serde_json::Value) -> Result<(), Error> {
+        let addr = contract
+            .get("address")
+            .and_then(|a| a.as_str())
+            .and_then(|a| a.parse::<Address>().ok())
+            .expect("Expect validated address for a contract");
+
+        let json = serde_json::to_vec(contract)
+            .map_err(|_| Error::IO(format!("Can't write contract for address {}", addr)))?;
+        self.db
+            .put(&addr, &json)
+            .map_err(|e| Error::IO(e.to_string()))
+    }
+
+    fn list(&self) -> Vec<serde_json::Value> {
+        self.db
+            .iterator(IteratorMode::Start)
+            .filter_map(|(addr, val)| match serde_json::from_slice(&val) {
+                Ok(contract) => Some(contract),
+                Err(_) => {
+                    log::info!("Invalid contract format for key: {:?}", addr);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn delete(&self, addr: &Address) -> Result<(), Error> {
+        if self.get(addr).is_none() {
+            return Err(Error::NotFound(addr.to_string()));
+        }
+
+        self.db.delete(addr).map_err(|e| Error::IO(e.to_string()))
+    }
+
+    fn get(&self, addr: &Address) -> Option<serde_json::Value> {
+        match self.db.get(addr) {
+            Ok(Some(val)) => serde_json::from_slice(&val).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_address, TEST_ADDRESS};
+    use serde_json::json;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_add_and_list_contracts() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = DbContractStorage::new(dir.path()).unwrap();
+        let contract = json!({
+            "address": TEST_ADDRESS,
+            "name": "Token",
+            "abi": []
+        });
+
+        storage.add(&contract).unwrap();
+        storage.add(&contract).unwrap();
+        assert_eq!(storage.list(), vec![contract]);
+    }
+
+    #[test]
+    fn should_get_and_delete_contract() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = DbContractStorage::new(dir.path()).unwrap();
+        let addr = test_address();
+        let contract = json!({
+            "address": TEST_ADDRESS,
+            "name": "Token",
+            "abi": []
+        });
+
+        storage.add(&contract).unwrap();
+        assert_eq!(storage.get(&addr), Some(contract));
+
+        storage.delete(&addr).unwrap();
+        assert_eq!(storage.get(&addr), None);
+        assert!(storage.list().is_empty());
+    }
+}
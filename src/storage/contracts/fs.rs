@@ -0,0 +1,128 @@
+//! # Contracts storage within filesystem
+
+use super::{contract_address, ContractStorage};
+use crate::contract::Error;
+use crate::core::Address;
+use crate::storage::write_atomic;
+use glob::glob;
+use hex;
+use serde_json;
+use std::fs::{self, read_dir, File};
+use std::path::{Path, PathBuf};
+
+/// Filesystem storage for contracts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsContractStorage {
+    dir: PathBuf,
+}
+
+impl FsContractStorage {
+    /// Initialize new contracts service for a dir
+    pub fn new(dir: PathBuf) -> FsContractStorage {
+        FsContractStorage { dir }
+    }
+
+    /// Path of contract file for address: existing file, which name
+    /// may differ in case or `0x` prefix, or `<address>.json` for a new one
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - contract address
+    ///
+    fn build_path(&self, addr: &Address) -> PathBuf {
+        let path = self.dir.join(format!("{}.json", addr));
+        if path.exists() {
+            return path;
+        }
+
+        let expected = format!("{}.json", hex::encode(addr));
+        read_dir(&self.dir)
+            .into_iter()
+            .flat_map(|entries| entries.filter_map(Result::ok))
+            .map(|entry| entry.path())
+            .find(|p| {
+                p.file_name().and_then(|n| n.to_str()).map_or(false, |n| {
+                    n.to_lowercase().trim_start_matches("0x") == expected
+                })
+            })
+            .unwrap_or(path)
+    }
+
+    fn read_json(path: &Path) -> Result<serde_json::Value, Error> {
+        match File::open(path) {
+            Ok(f) => serde_json::from_reader(f)
+                .or_else(|_| Err(Error::IO("Can't read contract file".to_string()))),
+            Err(_) => Err(Error::IO("Can't open contract file".to_string())),
+        }
+    }
+}
+
+impl ContractStorage for FsContractStorage {
+    fn put(&self, contract: &serde_json::Value) -> Result<(), Error> {
+        let addr = contract_address(contract).expect("Expect validated address for a contract");
+
+        let json = serde_json::to_vec_pretty(contract)
+            .map_err(|_| Error::IO(format!("Can't write contract for address {}", addr)))?;
+        write_atomic(&self.build_path(&addr), &json)?;
+
+        Ok(())
+    }
+
+    fn list(&self) -> Vec<serde_json::Value> {
+        let files = glob(&format!("{}/*.json", &self.dir.to_str().unwrap())).unwrap();
+
+        files
+            .filter(|x| x.is_ok())
+            .map(|x| FsContractStorage::read_json(x.unwrap().as_path()))
+            .filter(|x| x.is_ok())
+            .map(|x| x.unwrap())
+            .collect()
+    }
+
+    fn delete(&self, addr: &Address) -> Result<(), Error> {
+        let path = self.build_path(addr);
+        if !path.exists() {
+            return Err(Error::NotFound(addr.to_string()));
+        }
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_address, TEST_ADDRESS};
+    use serde_json::json;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_replace_contract_stored_under_checksummed_name() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = FsContractStorage::new(dir.path().to_path_buf());
+        let filename = format!("{}.json", test_address().to_checksum(None));
+        let contract = json!({
+            "address": TEST_ADDRESS,
+            "name": "Token",
+            "abi": []
+        });
+        fs::write(dir.path().join(&filename), contract.to_string()).unwrap();
+
+        let mut renamed = contract.clone();
+        renamed["name"] = json!("Renamed");
+        storage.put(&renamed).unwrap();
+
+        let files: Vec<_> = read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            FsContractStorage::read_json(&dir.path().join(&filename)).unwrap(),
+            renamed
+        );
+        assert_eq!(storage.get(&test_address()), Some(renamed));
+
+        storage.delete(&test_address()).unwrap();
+        assert!(!dir.path().join(&filename).exists());
+        assert!(storage.delete(&test_address()).is_err());
+    }
+}
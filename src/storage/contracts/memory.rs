@@ -0,0 +1,163 @@
+//! # Contracts storage in memory
+
+use super::{contract_address, ContractStorage};
+use crate::contract::Error;
+use crate::core::Address;
+use serde_json;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// In memory storage for contracts, nothing is persisted
+///
+#[derive(Debug, Default)]
+pub struct MemoryContractStorage {
+    /// Contracts by address
+    contracts: RwLock<BTreeMap<String, serde_json::Value>>,
+}
+
+impl ContractStorage for MemoryContractStorage {
+    fn put(&self, contract: &serde_json::Value) -> Result<(), Error> {
+        let addr = contract
+            .get("address")
+            .expect("Expect address for a contract")
+            .as_str()
+            .expect("Expect address be convertible to a string");
+
+        self.contracts
+            .write()
+            .unwrap()
+            .insert(addr.to_string(), contract.clone());
+
+        Ok(())
+    }
+
+    fn list(&self) -> Vec<serde_json::Value> {
+        self.contracts.read().unwrap().values().cloned().collect()
+    }
+
+    fn delete(&self, addr: &Address) -> Result<(), Error> {
+        let mut contracts = self.contracts.write().unwrap();
+        let len = contracts.len();
+        contracts.retain(|_, c| contract_address(c) != Some(*addr));
+
+        if contracts.len() == len {
+            return Err(Error::NotFound(addr.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_address, TEST_ADDRESS};
+    use serde_json::json;
+
+    #[test]
+    fn should_add_and_list_contracts() {
+        let storage = MemoryContractStorage::default();
+        let contract = json!({
+            "address": TEST_ADDRESS,
+            "name": "Token",
+            "abi": []
+        });
+
+        storage.add(&contract).unwrap();
+        assert_eq!(storage.list(), vec![contract]);
+    }
+
+    #[test]
+    fn should_reject_invalid_contract() {
+        let storage = MemoryContractStorage::default();
+
+        assert!(storage.add(&json!({ "abi": [] })).is_err());
+        assert!(storage
+            .add(&json!({
+                "address": TEST_ADDRESS,
+                "abi": [{
+                    "type": "function",
+                    "name": "f",
+                    "inputs": [{ "name": "a", "type": "foo" }],
+                    "outputs": [],
+                    "constant": false
+                }]
+            }))
+            .is_err());
+        assert!(storage
+            .add(&json!({
+                "address": TEST_ADDRESS,
+                "abi": {}
+            }))
+            .is_err());
+        assert!(storage.list().is_empty());
+    }
+
+    #[test]
+    fn should_normalize_contract() {
+        let storage = MemoryContractStorage::default();
+        storage
+            .add(&json!({
+                "address": "0xC0DE379B51D582E1600C76DD1EFEE8ED024B844A",
+                "abi": [],
+                "bytecode": "6080AB",
+                "extra": true
+            }))
+            .unwrap();
+
+        assert_eq!(
+            storage.list(),
+            vec![json!({
+                "address": TEST_ADDRESS,
+                "name": "",
+                "abi": [],
+                "bytecode": "0x6080ab"
+            })]
+        );
+    }
+
+    #[test]
+    fn should_report_address_conflict() {
+        let storage = MemoryContractStorage::default();
+        let contract = json!({
+            "address": TEST_ADDRESS,
+            "name": "Token",
+            "abi": []
+        });
+
+        storage.add(&contract).unwrap();
+        storage.add(&contract).unwrap();
+        match storage.add(&json!({
+            "address": TEST_ADDRESS,
+            "name": "Other",
+            "abi": []
+        })) {
+            Err(Error::AlreadyExists(_)) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_update_and_delete_contract() {
+        let storage = MemoryContractStorage::default();
+        let addr = test_address();
+        storage
+            .add(&json!({
+                "address": TEST_ADDRESS,
+                "name": "Token",
+                "abi": []
+            }))
+            .unwrap();
+
+        storage
+            .update(&addr, &json!({ "name": "Coin", "address": "0x00" }))
+            .unwrap();
+        assert!(storage.find_by_name("Token").is_empty());
+        assert_eq!(storage.find_by_name("Coin").len(), 1);
+        assert_eq!(storage.get(&addr).unwrap()["address"], TEST_ADDRESS);
+
+        storage.delete(&addr).unwrap();
+        assert!(storage.get(&addr).is_none());
+        assert!(storage.delete(&addr).is_err());
+        assert!(storage.update(&addr, &json!({})).is_err());
+    }
+}
@@ -1,26 +1,123 @@
-//! # Contracts utils
+//! # Storage for contracts
+///
+/// Provides 3 variants of storage:
+/// * backed with `db`
+/// * plain filesystem
+/// * in memory
+///
+mod db;
+mod fs;
+mod memory;
 
-use crate::contract::Error;
+pub use self::db::DbContractStorage;
+pub use self::fs::FsContractStorage;
+pub use self::memory::MemoryContractStorage;
+use crate::contract::{Contract, Error};
 use crate::core::Address;
-use glob::glob;
-use serde_json;
-use std::fs::File;
-use std::path::{Path, PathBuf};
-
-/// Contracts Service
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ContractStorage {
-    dir: PathBuf,
-}
+use hex;
+use serde_json::{self, Map, Value};
+
+/// Storage for contracts
+///
+pub trait ContractStorage: Send + Sync {
+    /// Write contract, replacing existing one with the same address
+    ///
+    /// # Arguments:
+    ///
+    ///  * contract - normalized contract JSON, see `validate`
+    ///
+    fn put(&self, contract: &Value) -> Result<(), Error>;
+
+    /// List all available contracts
+    fn list(&self) -> Vec<Value>;
+
+    /// Delete contract
+    ///
+    /// # Arguments:
+    ///
+    ///  * addr - contract address
+    ///
+    fn delete(&self, addr: &Address) -> Result<(), Error>;
+
+    /// Add new contract to storage.
+    /// Fails if another contract is already stored for the same address.
+    ///
+    /// # Arguments:
+    ///
+    ///  * contract - contract JSON with `address` and `abi` fields
+    ///
+    fn add(&self, contract: &Value) -> Result<(), Error> {
+        let contract = self.validate(contract)?;
+        let addr = contract_address(&contract).expect("Expect validated address");
+
+        match self.get(&addr) {
+            Some(ref existing) if *existing != contract => {
+                Err(Error::AlreadyExists(addr.to_string()))
+            }
+            _ => self.put(&contract),
+        }
+    }
+
+    /// Find contract by address
+    ///
+    /// # Arguments:
+    ///
+    ///  * addr - contract address
+    ///
+    fn get(&self, addr: &Address) -> Option<Value> {
+        self.list()
+            .into_iter()
+            .find(|c| contract_address(c) == Some(*addr))
+    }
+
+    /// Find contracts by name
+    ///
+    /// # Arguments:
+    ///
+    ///  * name - contract name
+    ///
+    fn find_by_name(&self, name: &str) -> Vec<Value> {
+        self.list()
+            .into_iter()
+            .filter(|c| c.get("name").and_then(|n| n.as_str()) == Some(name))
+            .collect()
+    }
+
+    /// Update fields of existing contract, address can't be changed
+    ///
+    /// # Arguments:
+    ///
+    ///  * addr - contract address
+    ///  * changes - JSON object with fields to replace
+    ///
+    fn update(&self, addr: &Address, changes: &Value) -> Result<(), Error> {
+        let mut contract = self
+            .get(addr)
+            .ok_or_else(|| Error::NotFound(addr.to_string()))?;
+        let changes = changes
+            .as_object()
+            .ok_or_else(|| Error::InvalidContract("Invalid data format".to_string()))?;
+
+        for (k, v) in changes {
+            if k != "address" {
+                contract[k] = v.clone();
+            }
+        }
 
-impl ContractStorage {
-    /// Initialize new contracts service for a dir
-    pub fn new(dir: PathBuf) -> ContractStorage {
-        ContractStorage { dir }
+        self.put(&self.validate(&contract)?)
     }
 
-    /// Validate contract structure
-    pub fn validate(&self, contract: &serde_json::Value) -> Result<(), Error> {
+    /// Validate contract structure and ABI
+    ///
+    /// # Arguments:
+    ///
+    ///  * contract - contract JSON to validate
+    ///
+    /// # Return:
+    ///
+    /// Normalized contract JSON with `address`, `name`, `abi` and optional `bytecode` fields
+    ///
+    fn validate(&self, contract: &Value) -> Result<Value, Error> {
         if !contract.is_object() {
             return Err(Error::InvalidContract("Invalid data format".to_string()));
         }
@@ -31,52 +128,99 @@ impl ContractStorage {
         if !addr.is_string() {
             return Err(Error::InvalidContract("Invalid address format".to_string()));
         }
-        match addr.as_str().unwrap().parse::<Address>() {
-            Ok(_) => {}
+        let addr = match addr.as_str().unwrap().parse::<Address>() {
+            Ok(addr) => addr,
             Err(_) => return Err(Error::InvalidContract("Can't parse address".to_string())),
-        }
-        Ok(())
-    }
+        };
+
+        let name = match contract.get("name") {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(name)) => name.clone(),
+            Some(_) => return Err(Error::InvalidContract("Invalid name format".to_string())),
+        };
 
-    /// Add new contract to storage
-    pub fn add(&self, contract: &serde_json::Value) -> Result<(), Error> {
-        self.validate(contract)?;
-        let addr = contract
-            .get("address")
-            .expect("Expect address for a contract")
-            .as_str()
-            .expect("Expect address be convertible to a string");
-
-        let mut filename: PathBuf = self.dir.clone();
-        filename.push(format!("{}.json", addr));
-
-        let mut f = File::create(filename.as_path())?;
-        match serde_json::to_writer_pretty(&mut f, contract) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(Error::IO(format!(
-                "Can't write contract for address {}",
-                addr
-            ))),
+        let abi = match contract.get("abi") {
+            Some(abi) if abi.is_array() => abi,
+            Some(_) => return Err(Error::InvalidContract("ABI should be an array".to_string())),
+            None => return Err(Error::InvalidContract("Missing ABI".to_string())),
+        };
+        let json = serde_json::to_vec(abi).map_err(|e| Error::InvalidContract(e.to_string()))?;
+        Contract::try_from(&json).map_err(|e| match e {
+            Error::InvalidContract(msg) => Error::InvalidContract(format!("Invalid ABI: {}", msg)),
+            e => e,
+        })?;
+
+        let mut normalized = Map::new();
+        normalized.insert("address".to_string(), Value::String(addr.to_string()));
+        normalized.insert("name".to_string(), Value::String(name));
+        normalized.insert("abi".to_string(), abi.clone());
+
+        match contract.get("bytecode") {
+            None | Some(Value::Null) => {}
+            Some(Value::String(code)) => {
+                let code = code.trim_start_matches("0x");
+                if hex::decode(code).is_err() {
+                    return Err(Error::InvalidContract(
+                        "Bytecode should be a hex string".to_string(),
+                    ));
+                }
+                normalized.insert(
+                    "bytecode".to_string(),
+                    Value::String(format!("0x{}", code.to_lowercase())),
+                );
+            }
+            Some(_) => {
+                return Err(Error::InvalidContract(
+                    "Bytecode should be a hex string".to_string(),
+                ))
+            }
         }
-    }
 
-    /// List all available contracts
-    pub fn list(&self) -> Vec<serde_json::Value> {
-        let files = glob(&format!("{}/*.json", &self.dir.to_str().unwrap())).unwrap();
-
-        files
-            .filter(|x| x.is_ok())
-            .map(|x| ContractStorage::read_json(x.unwrap().as_path()))
-            .filter(|x| x.is_ok())
-            .map(|x| x.unwrap())
-            .collect()
+        Ok(Value::Object(normalized))
     }
+}
+
+/// Copy contracts missing in target storage, e.g. JSON files
+/// of filesystem storage into database storage.
+/// Invalid contracts are skipped.
+///
+/// # Arguments:
+///
+///  * from - source storage
+///  * to - target storage
+///
+/// # Return:
+///
+/// Number of copied contracts
+///
+pub fn import_contracts(
+    from: &dyn ContractStorage,
+    to: &dyn ContractStorage,
+) -> Result<usize, Error> {
+    let mut imported = 0;
+    for contract in from.list() {
+        let contract = match to.validate(&contract) {
+            Ok(contract) => contract,
+            Err(e) => {
+                log::warn!("Skipped invalid contract on import: {}", e);
+                continue;
+            }
+        };
 
-    fn read_json(path: &Path) -> Result<serde_json::Value, Error> {
-        match File::open(path) {
-            Ok(f) => serde_json::from_reader(f)
-                .or_else(|_| Err(Error::IO("Can't read contract file".to_string()))),
-            Err(_) => Err(Error::IO("Can't open contract file".to_string())),
+        let addr = contract_address(&contract).expect("Expect validated address");
+        if to.get(&addr).is_none() {
+            to.put(&contract)?;
+            imported += 1;
         }
     }
+
+    Ok(imported)
+}
+
+/// Extract address of a contract
+fn contract_address(contract: &Value) -> Option<Address> {
+    contract
+        .get("address")
+        .and_then(|a| a.as_str())
+        .and_then(|a| a.parse::<Address>().ok())
 }
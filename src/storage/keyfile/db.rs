@@ -1,11 +1,11 @@
 //! # Storage for `Keystore` files
 
 use super::error::KeystoreError;
-use super::{generate_filename, AccountInfo, KeyfileStorage};
+use super::{AccountInfo, KeyfileStorage, WatchOnlyAccount};
 use crate::core::Address;
 use crate::keystore::KeyFile;
 use crate::util;
-use rocksdb::{IteratorMode, DB};
+use rocksdb::{IteratorMode, WriteBatch, WriteOptions, DB};
 use serde_json;
 use std::path::Path;
 use std::str;
@@ -22,6 +22,11 @@ pub struct DbStorage {
 ///
 const SEPARATOR: &str = "<|>";
 
+/// Key prefix for watch-only accounts
+/// `key = WATCH_ONLY_PREFIX + <address>`
+///
+const WATCH_ONLY_PREFIX: &[u8] = b"watch-only:";
+
 impl DbStorage {
     /// Create new database storage
     /// Use specified directory as parent folder
@@ -55,21 +60,56 @@ impl DbStorage {
 
         Ok((arr[0].to_string(), json))
     }
+
+    /// Apply batch atomically, waiting until it is flushed to disk.
+    /// Interrupted batches are discarded by write-ahead log replay on open.
+    ///
+    /// # Arguments:
+    ///
+    /// * batch - set of updates
+    ///
+    fn write(&self, batch: WriteBatch) -> Result<(), KeystoreError> {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(true);
+        self.db.write_opt(batch, &opts)?;
+
+        Ok(())
+    }
+
+    /// Creates database key for watch-only account
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    ///
+    fn watch_only_key(addr: &Address) -> Vec<u8> {
+        let mut key = WATCH_ONLY_PREFIX.to_vec();
+        key.extend_from_slice(addr);
+        key
+    }
 }
 
 impl KeyfileStorage for DbStorage {
-    fn put(&self, kf: &KeyFile) -> Result<(), KeystoreError> {
+    fn put_with_name(&self, kf: &KeyFile, filename: &str) -> Result<(), KeystoreError> {
+        if let Ok((prev, _)) = self.search_by_address(&kf.address) {
+            if prev.filename != filename {
+                return Err(KeystoreError::AlreadyExists(kf.address.to_string()));
+            }
+        }
+
         let json = serde_json::to_string(&kf)?;
-        let val = generate_filename(&kf.uuid.to_string()) + SEPARATOR + &json;
-        self.db.put(&kf.address, val.as_bytes())?;
+        let val = filename.to_string() + SEPARATOR + &json;
+        let mut batch = WriteBatch::default();
+        batch.put(&kf.address, val.as_bytes())?;
 
-        Ok(())
+        self.write(batch)
     }
 
     fn delete(&self, addr: &Address) -> Result<(), KeystoreError> {
-        self.db.delete(addr)?;
+        let mut batch = WriteBatch::default();
+        batch.delete(addr)?;
 
-        Ok(())
+        self.write(batch)
     }
 
     fn search_by_address(&self, addr: &Address) -> Result<(AccountInfo, KeyFile), KeystoreError> {
@@ -88,19 +128,19 @@ impl KeyfileStorage for DbStorage {
     }
 
     fn hide(&self, addr: &Address) -> Result<bool, KeystoreError> {
-        let (_, mut kf) = self.search_by_address(addr)?;
+        let (info, mut kf) = self.search_by_address(addr)?;
 
         kf.visible = Some(false);
-        self.put(&kf)?;
+        self.put_with_name(&kf, &info.filename)?;
 
         Ok(true)
     }
 
     fn unhide(&self, addr: &Address) -> Result<bool, KeystoreError> {
-        let (_, mut kf) = self.search_by_address(addr)?;
+        let (info, mut kf) = self.search_by_address(addr)?;
 
         kf.visible = Some(true);
-        self.put(&kf)?;
+        self.put_with_name(&kf, &info.filename)?;
 
         Ok(true)
     }
@@ -109,6 +149,14 @@ impl KeyfileStorage for DbStorage {
         let mut accounts = vec![];
 
         for (addr, val) in self.db.iterator(IteratorMode::Start) {
+            if addr.starts_with(WATCH_ONLY_PREFIX) {
+                match serde_json::from_slice::<WatchOnlyAccount>(&val) {
+                    Ok(account) => accounts.push(AccountInfo::from(account)),
+                    Err(_) => log::info!("Invalid watch-only account format for key: {:?}", addr),
+                }
+                continue;
+            }
+
             let vec = str::from_utf8(&val)?;
             let (filename, json) = DbStorage::split(vec)?;
             match KeyFile::decode(&json) {
@@ -138,7 +186,7 @@ impl KeyfileStorage for DbStorage {
         name: Option<String>,
         desc: Option<String>,
     ) -> Result<(), KeystoreError> {
-        let (_, mut kf) = self.search_by_address(addr)?;
+        let (info, mut kf) = self.search_by_address(addr)?;
 
         if name.is_some() {
             kf.name = name;
@@ -148,13 +196,31 @@ impl KeyfileStorage for DbStorage {
             kf.description = desc;
         };
 
-        self.put(&kf)
+        self.put_with_name(&kf, &info.filename)
+    }
+
+    fn put_watch_only(&self, account: &WatchOnlyAccount) -> Result<(), KeystoreError> {
+        let json = serde_json::to_string(account)?;
+        let mut batch = WriteBatch::default();
+        batch.put(DbStorage::watch_only_key(&account.address), json.as_bytes())?;
+
+        self.write(batch)
+    }
+
+    fn search_watch_only(&self, addr: &Address) -> Result<WatchOnlyAccount, KeystoreError> {
+        let val = self
+            .db
+            .get(DbStorage::watch_only_key(addr))?
+            .ok_or_else(|| KeystoreError::NotFound(format!("{}", addr)))?;
+
+        Ok(serde_json::from_slice(&val)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::{keyfile, test_address};
 
     #[test]
     fn should_split() {
@@ -217,6 +283,50 @@ mod tests {
         )
     }
 
+    #[test]
+    fn should_list_watch_only() {
+        let dir = tempdir::TempDir::new("jade").unwrap();
+        let storage = DbStorage::new(dir.path()).unwrap();
+        let account = WatchOnlyAccount {
+            address: test_address(),
+            name: "cold".to_string(),
+            tags: vec!["treasury".to_string()],
+            ..Default::default()
+        };
+
+        storage.put_watch_only(&account).unwrap();
+
+        assert_eq!(
+            storage.search_watch_only(&account.address).unwrap(),
+            account
+        );
+        assert!(storage.is_watch_only(&account.address));
+        assert!(storage.search_by_address(&account.address).is_err());
+
+        let accounts = storage.list_accounts(false).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert!(accounts[0].is_watch_only);
+        assert_eq!(accounts[0].tags, account.tags);
+    }
+
+    #[test]
+    fn should_keep_synced_writes_after_reopen() {
+        let dir = tempdir::TempDir::new("jade").unwrap();
+        let kf = keyfile();
+
+        {
+            let storage = DbStorage::new(dir.path()).unwrap();
+            storage.put_with_name(&kf, "keyfile").unwrap();
+            storage.hide(&kf.address).unwrap();
+        }
+
+        let storage = DbStorage::new(dir.path()).unwrap();
+        let (info, found) = storage.search_by_address(&kf.address).unwrap();
+        assert_eq!(found, kf);
+        assert_eq!(info.filename, "keyfile");
+        assert!(info.is_hidden);
+    }
+
     #[cfg(feature = "nightly")]
     mod benches {
         use super::*;
@@ -14,6 +14,9 @@ pub enum KeystoreError {
 
     /// `KeyFile` not found
     NotFound(String),
+
+    /// `KeyFile` for address is already stored under another name
+    AlreadyExists(String),
 }
 
 impl From<rocksdb::Error> for KeystoreError {
@@ -51,6 +54,9 @@ impl fmt::Display for KeystoreError {
         match *self {
             KeystoreError::StorageError(ref str) => write!(f, "KeyFile storage error: {}", str),
             KeystoreError::NotFound(ref str) => write!(f, "Missing KeyFile for address: {}", str),
+            KeystoreError::AlreadyExists(ref str) => {
+                write!(f, "KeyFile already exists for address: {}", str)
+            }
         }
     }
 }
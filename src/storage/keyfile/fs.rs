@@ -1,21 +1,41 @@
 //! # `KeyFile` storage within filesystem
 
 use super::error::KeystoreError;
-use super::{generate_filename, AccountInfo, KeyfileStorage};
+use super::{AccountInfo, KeyfileStorage, WatchOnlyAccount};
 use crate::core::Address;
 use crate::keystore::try_extract_address;
 use crate::keystore::KeyFile;
+use crate::storage::{write_atomic, TMP_SUFFIX};
 
 use serde_json;
+use std::collections::BTreeMap;
 use std::fs::{self, read_dir, File};
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+/// Subfolder for watch-only accounts
+const WATCH_ONLY_DIR: &str = "watch-only";
+
+/// Cached info of `KeyFiles` inside storage dir
+#[derive(Debug, Default)]
+struct Index {
+    /// `AccountInfo` (including filename) by address
+    accounts: BTreeMap<Address, AccountInfo>,
+
+    /// Modification time of storage dir at the moment of last sync
+    modified: Option<SystemTime>,
+}
 
 /// Filesystem storage for `KeyFiles`
 ///
 pub struct FsStorage {
     /// Parent directory for storage
     base_path: PathBuf,
+
+    /// Address index, rebuilt when storage dir is changed externally
+    index: RwLock<Index>,
 }
 
 impl FsStorage {
@@ -30,9 +50,128 @@ impl FsStorage {
     where
         P: AsRef<Path>,
     {
-        FsStorage {
+        let st = FsStorage {
             base_path: dir.as_ref().to_owned(),
+            index: RwLock::new(Index::default()),
+        };
+        FsStorage::recover(&st.base_path);
+        FsStorage::recover(&st.build_path(WATCH_ONLY_DIR));
+        if let Err(e) = st.rebuild_index() {
+            log::info!("Can't build index for {:?}: {}", st.base_path, e);
         }
+
+        st
+    }
+
+    /// Complete or discard writes interrupted by a crash.
+    /// Temporary file replaces its target only if it is fully written
+    /// and the target is missing or unreadable, otherwise it is removed.
+    ///
+    /// # Arguments:
+    ///
+    /// * dir - directory to check
+    ///
+    fn recover(dir: &Path) {
+        let entries = match read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let tmp = entry.path();
+            let target = match tmp.to_str() {
+                Some(p) if p.ends_with(TMP_SUFFIX) => {
+                    PathBuf::from(&p[..p.len() - TMP_SUFFIX.len()])
+                }
+                _ => continue,
+            };
+
+            if FsStorage::is_complete(&tmp) && !FsStorage::is_complete(&target) {
+                match fs::rename(&tmp, &target) {
+                    Ok(_) => log::warn!("Recovered interrupted write of: {:?}", target),
+                    Err(e) => log::error!("Can't recover {:?}: {}", target, e),
+                }
+            } else {
+                log::warn!("Discarded interrupted write of: {:?}", target);
+                fs::remove_file(&tmp).ok();
+            }
+        }
+    }
+
+    /// Check that file contains a valid keyfile or watch-only account
+    fn is_complete(path: &Path) -> bool {
+        let mut content = String::new();
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut content)) {
+            Ok(_) => {
+                KeyFile::decode(&content).is_ok()
+                    || serde_json::from_str::<WatchOnlyAccount>(&content).is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Modification time of storage dir
+    fn dir_modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.base_path)
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    /// Read all keyfiles inside storage dir into index
+    fn rebuild_index(&self) -> Result<(), KeystoreError> {
+        let modified = self.dir_modified();
+        let mut accounts = BTreeMap::new();
+
+        for entry in read_dir(&self.base_path)?.filter_map(Result::ok) {
+            let path = entry.path();
+            if path.is_dir() || path.to_string_lossy().ends_with(TMP_SUFFIX) {
+                continue;
+            }
+
+            let name = match path.file_name().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => {
+                    log::info!("Corrupted filename for: {:?}", entry.file_name());
+                    continue;
+                }
+            };
+
+            match FsStorage::read_keyfile(&path) {
+                Ok(kf) => {
+                    let mut info = AccountInfo::from(kf.clone());
+                    info.filename = name;
+                    accounts.insert(kf.address, info);
+                }
+                Err(_) => log::info!("Invalid keystore file format for: {:?}", entry.file_name()),
+            }
+        }
+
+        *self.index.write().unwrap() = Index { accounts, modified };
+
+        Ok(())
+    }
+
+    /// Rebuild index if storage dir was changed since last sync
+    fn refresh_index(&self) -> Result<(), KeystoreError> {
+        let modified = self.index.read().unwrap().modified;
+        if modified.is_none() || modified != self.dir_modified() {
+            self.rebuild_index()?;
+        }
+
+        Ok(())
+    }
+
+    /// Read and decode keyfile
+    ///
+    /// # Arguments:
+    ///
+    /// * path - path to keyfile
+    ///
+    fn read_keyfile(path: &Path) -> Result<KeyFile, KeystoreError> {
+        let mut content = String::new();
+        File::open(path)?.read_to_string(&mut content)?;
+
+        Ok(KeyFile::decode(&content)?)
     }
 
     /// Hides/unhides `Keyfile` for specified `Address`
@@ -46,7 +185,6 @@ impl FsStorage {
         let (info, mut kf) = self.search_by_address(addr)?;
 
         kf.visible = Some(is_visible);
-        self.delete(&kf.address)?;
 
         self.put_with_name(&kf, &info.filename)
     }
@@ -63,30 +201,67 @@ impl FsStorage {
         path
     }
 
-    /// Put new `Keyfile` with specified name inside storage
-    /// Uses absolute `path` appended with `KeyFile` name
+    /// Creates path for watch-only account file
     ///
     /// # Arguments:
     ///
-    /// * kf - target `Keyfile`
-    /// * name - filename
+    /// * addr - account address
     ///
-    fn put_with_name(&self, kf: &KeyFile, name: &str) -> Result<(), KeystoreError> {
-        let json = serde_json::to_string(&kf)?;
-        let path = self.build_path(name);
+    fn build_watch_only_path(&self, addr: &Address) -> PathBuf {
+        let mut path = self.build_path(WATCH_ONLY_DIR);
+        path.push(format!("{}.json", addr));
+        path
+    }
 
-        let mut file = File::create(&path)?;
-        file.write_all(json.as_ref()).ok();
+    /// Lists all watch-only accounts inside storage
+    fn list_watch_only(&self) -> Vec<AccountInfo> {
+        let entries = match read_dir(self.build_path(WATCH_ONLY_DIR)) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
 
-        Ok(())
+        let mut accounts = vec![];
+        for entry in entries.filter_map(Result::ok) {
+            let account = File::open(entry.path())
+                .map_err(KeystoreError::from)
+                .and_then(|f| {
+                    serde_json::from_reader::<_, WatchOnlyAccount>(f).map_err(From::from)
+                });
+
+            match account {
+                Ok(account) => accounts.push(AccountInfo::from(account)),
+                Err(_) => log::info!(
+                    "Invalid watch-only account format for: {:?}",
+                    entry.file_name()
+                ),
+            }
+        }
+
+        accounts
     }
 }
 
 impl KeyfileStorage for FsStorage {
-    fn put(&self, kf: &KeyFile) -> Result<(), KeystoreError> {
-        let name = generate_filename(&kf.uuid.to_string());
+    fn put_with_name(&self, kf: &KeyFile, name: &str) -> Result<(), KeystoreError> {
+        let json = serde_json::to_string(&kf)?;
+        let path = self.build_path(name);
+        self.refresh_index()?;
 
-        self.put_with_name(kf, &name)
+        let mut index = self.index.write().unwrap();
+        if let Some(prev) = index.accounts.get(&kf.address) {
+            if prev.filename != name {
+                return Err(KeystoreError::AlreadyExists(kf.address.to_string()));
+            }
+        }
+
+        write_atomic(&path, json.as_ref())?;
+
+        let mut info = AccountInfo::from(kf.clone());
+        info.filename = name.to_string();
+        index.accounts.insert(kf.address, info);
+        index.modified = self.dir_modified();
+
+        Ok(())
     }
 
     fn delete(&self, addr: &Address) -> Result<(), KeystoreError> {
@@ -94,7 +269,13 @@ impl KeyfileStorage for FsStorage {
         let path = self.build_path(&info.filename);
 
         match fs::remove_file(path) {
-            Ok(_) => Ok(()),
+            Ok(_) => {
+                let mut index = self.index.write().unwrap();
+                index.accounts.remove(addr);
+                index.modified = self.dir_modified();
+
+                Ok(())
+            }
             Err(_) => Err(KeystoreError::StorageError(format!(
                 "Can't delete KeyFile for address: {}",
                 addr
@@ -103,35 +284,29 @@ impl KeyfileStorage for FsStorage {
     }
 
     fn search_by_address(&self, addr: &Address) -> Result<(AccountInfo, KeyFile), KeystoreError> {
-        let entries = fs::read_dir(&self.base_path)?;
-
-        for entry in entries {
-            let path = entry?.path();
+        self.refresh_index()?;
 
-            if path.is_dir() {
-                continue;
+        for attempt in 0..2 {
+            if attempt > 0 {
+                // Indexed file was changed without touching the dir, resync
+                self.rebuild_index()?;
             }
 
-            let mut file = fs::File::open(&path)?;
-            let mut content = String::new();
+            let filename = match self.index.read().unwrap().accounts.get(addr) {
+                Some(info) => info.filename.clone(),
+                None => break,
+            };
 
-            if file.read_to_string(&mut content).is_err() {
-                continue;
+            let mut content = String::new();
+            if let Ok(mut file) = File::open(self.build_path(&filename)) {
+                file.read_to_string(&mut content).ok();
             }
 
             match try_extract_address(&content) {
                 Some(a) if a == *addr => {
                     let kf = KeyFile::decode(&content)?;
                     let mut info = AccountInfo::from(kf.clone());
-                    info.filename = match path.file_name().and_then(|s| s.to_str()) {
-                        Some(s) => s.to_string(),
-                        None => {
-                            return Err(KeystoreError::StorageError(format!(
-                                "Invalid filename format for address {}",
-                                addr
-                            )));
-                        }
-                    };
+                    info.filename = filename;
 
                     return Ok((info, kf));
                 }
@@ -143,43 +318,38 @@ impl KeyfileStorage for FsStorage {
     }
 
     fn list_accounts(&self, show_hidden: bool) -> Result<Vec<AccountInfo>, KeystoreError> {
-        let mut accounts = vec![];
-        for e in read_dir(&self.base_path)? {
-            if e.is_err() {
-                continue;
-            }
-            let entry = e.unwrap();
-            let mut content = String::new();
-            if let Ok(mut keyfile) = File::open(entry.path()) {
-                if keyfile.read_to_string(&mut content).is_err() {
-                    continue;
-                }
-
-                match KeyFile::decode(&content) {
-                    Ok(kf) => {
-                        if kf.visible.is_none() || kf.visible.unwrap() || show_hidden {
-                            let mut info = AccountInfo::from(kf);
-                            match entry.path().file_name().and_then(|s| s.to_str()) {
-                                Some(name) => {
-                                    info.filename = name.to_string();
-                                    accounts.push(info);
-                                }
-                                None => {
-                                    log::info!("Corrupted filename for: {:?}", entry.file_name())
-                                }
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        log::info!("Invalid keystore file format for: {:?}", entry.file_name())
-                    }
-                }
-            }
-        }
+        self.refresh_index()?;
+
+        let mut accounts: Vec<AccountInfo> = self
+            .index
+            .read()
+            .unwrap()
+            .accounts
+            .values()
+            .filter(|info| !info.is_hidden || show_hidden)
+            .cloned()
+            .collect();
+        accounts.extend(self.list_watch_only());
 
         Ok(accounts)
     }
 
+    fn put_watch_only(&self, account: &WatchOnlyAccount) -> Result<(), KeystoreError> {
+        fs::create_dir_all(self.build_path(WATCH_ONLY_DIR))?;
+
+        let json = serde_json::to_string(account)?;
+        write_atomic(&self.build_watch_only_path(&account.address), json.as_ref())?;
+
+        Ok(())
+    }
+
+    fn search_watch_only(&self, addr: &Address) -> Result<WatchOnlyAccount, KeystoreError> {
+        match File::open(self.build_watch_only_path(addr)) {
+            Ok(f) => Ok(serde_json::from_reader(f)?),
+            Err(_) => Err(KeystoreError::NotFound(addr.to_string())),
+        }
+    }
+
     fn hide(&self, addr: &Address) -> Result<bool, KeystoreError> {
         self.toogle_visibility(addr, false)?;
 
@@ -207,8 +377,124 @@ impl KeyfileStorage for FsStorage {
         if desc.is_some() {
             kf.description = desc;
         };
-        self.delete(&kf.address)?;
 
         self.put_with_name(&kf, &info.filename)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::keyfile;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_keep_index_on_put_and_delete() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = FsStorage::new(dir.path());
+        let kf = keyfile();
+
+        storage.put_with_name(&kf, "first").unwrap();
+        storage.put_with_name(&kf, "first").unwrap();
+
+        let (info, found) = storage.search_by_address(&kf.address).unwrap();
+        assert_eq!(info.filename, "first");
+        assert_eq!(found, kf);
+        assert_eq!(storage.list_accounts(false).unwrap().len(), 1);
+
+        match storage.put_with_name(&kf, "second") {
+            Err(KeystoreError::AlreadyExists(_)) => {}
+            r => panic!("Expected AlreadyExists, got {:?}", r),
+        }
+        assert!(dir.path().join("first").exists());
+        assert!(!dir.path().join("second").exists());
+
+        storage.delete(&kf.address).unwrap();
+        assert!(storage.search_by_address(&kf.address).is_err());
+        assert!(storage.list_accounts(false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_rebuild_index_on_startup() {
+        let dir = TempDir::new("jade").unwrap();
+        let kf = keyfile();
+        FsStorage::new(dir.path()).put(&kf).unwrap();
+
+        let storage = FsStorage::new(dir.path());
+        assert!(storage
+            .index
+            .read()
+            .unwrap()
+            .accounts
+            .contains_key(&kf.address));
+        assert!(storage.search_by_address(&kf.address).is_ok());
+    }
+
+    #[test]
+    fn should_refresh_index_on_external_changes() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = FsStorage::new(dir.path());
+        let kf = keyfile();
+        storage.put_with_name(&kf, "keyfile").unwrap();
+
+        fs::remove_file(dir.path().join("keyfile")).unwrap();
+        assert!(storage.search_by_address(&kf.address).is_err());
+
+        let other = keyfile();
+        FsStorage::new(dir.path())
+            .put_with_name(&other, "other")
+            .unwrap();
+        assert!(storage.search_by_address(&other.address).is_ok());
+        assert_eq!(storage.list_accounts(false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_recover_interrupted_writes() {
+        let dir = TempDir::new("jade").unwrap();
+        let kf = keyfile();
+        let json = serde_json::to_string(&kf).unwrap();
+
+        // crashed before rename, target is missing
+        fs::write(dir.path().join("complete.tmp"), &json).unwrap();
+        // crashed while writing temporary file, target is intact
+        let other = keyfile();
+        FsStorage::new(dir.path())
+            .put_with_name(&other, "partial")
+            .unwrap();
+        fs::write(dir.path().join("partial.tmp"), &json[..json.len() / 2]).unwrap();
+
+        let storage = FsStorage::new(dir.path());
+        assert!(dir.path().join("complete").exists());
+        assert!(!dir.path().join("complete.tmp").exists());
+        assert!(!dir.path().join("partial.tmp").exists());
+        assert_eq!(storage.search_by_address(&kf.address).unwrap().1, kf);
+        assert_eq!(storage.search_by_address(&other.address).unwrap().1, other);
+    }
+
+    #[test]
+    fn should_keep_single_keyfile_on_interrupted_replace() {
+        let dir = TempDir::new("jade").unwrap();
+        let kf = keyfile();
+        FsStorage::new(dir.path())
+            .put_with_name(&kf, "keyfile")
+            .unwrap();
+
+        // same account re-encrypted with another passphrase,
+        // crashed before rename of a fully written file
+        let mut shaked = keyfile();
+        shaked.address = kf.address;
+        let json = serde_json::to_string(&shaked).unwrap();
+        fs::write(dir.path().join("keyfile.tmp"), &json).unwrap();
+
+        let storage = FsStorage::new(dir.path());
+        let files: Vec<_> = read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(storage.list_accounts(false).unwrap().len(), 1);
+        assert_eq!(storage.search_by_address(&kf.address).unwrap().1, kf);
+
+        storage.put_with_name(&shaked, "keyfile").unwrap();
+        let files: Vec<_> = read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(storage.search_by_address(&kf.address).unwrap().1, shaked);
+    }
+}
@@ -0,0 +1,212 @@
+//! # `KeyFile` storage in memory
+
+use super::error::KeystoreError;
+use super::{AccountInfo, KeyfileStorage, WatchOnlyAccount};
+use crate::core::Address;
+use crate::keystore::KeyFile;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// In memory storage for `KeyFiles`, nothing is persisted
+///
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    /// `KeyFile`s with their filenames by address
+    keyfiles: RwLock<BTreeMap<Address, (String, KeyFile)>>,
+
+    /// Watch-only accounts by address
+    watch_only: RwLock<BTreeMap<Address, WatchOnlyAccount>>,
+}
+
+impl MemoryStorage {
+    /// Create new empty `MemoryStorage`
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    /// Apply change to stored `KeyFile` for specified `Address`
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - target address
+    /// * f - change to apply
+    ///
+    fn modify<F>(&self, addr: &Address, f: F) -> Result<(), KeystoreError>
+    where
+        F: FnOnce(&mut KeyFile),
+    {
+        match self.keyfiles.write().unwrap().get_mut(addr) {
+            Some((_, kf)) => {
+                f(kf);
+                Ok(())
+            }
+            None => Err(KeystoreError::NotFound(addr.to_string())),
+        }
+    }
+}
+
+impl KeyfileStorage for MemoryStorage {
+    fn put_with_name(&self, kf: &KeyFile, filename: &str) -> Result<(), KeystoreError> {
+        let mut keyfiles = self.keyfiles.write().unwrap();
+        if let Some((prev, _)) = keyfiles.get(&kf.address) {
+            if prev != filename {
+                return Err(KeystoreError::AlreadyExists(kf.address.to_string()));
+            }
+        }
+        keyfiles.insert(kf.address, (filename.to_string(), kf.clone()));
+
+        Ok(())
+    }
+
+    fn delete(&self, addr: &Address) -> Result<(), KeystoreError> {
+        match self.keyfiles.write().unwrap().remove(addr) {
+            Some(_) => Ok(()),
+            None => Err(KeystoreError::StorageError(format!(
+                "Can't delete KeyFile for address: {}",
+                addr
+            ))),
+        }
+    }
+
+    fn hide(&self, addr: &Address) -> Result<bool, KeystoreError> {
+        self.modify(addr, |kf| kf.visible = Some(false))?;
+
+        Ok(true)
+    }
+
+    fn unhide(&self, addr: &Address) -> Result<bool, KeystoreError> {
+        self.modify(addr, |kf| kf.visible = Some(true))?;
+
+        Ok(true)
+    }
+
+    fn update(
+        &self,
+        addr: &Address,
+        name: Option<String>,
+        desc: Option<String>,
+    ) -> Result<(), KeystoreError> {
+        self.modify(addr, |kf| {
+            if name.is_some() {
+                kf.name = name;
+            };
+
+            if desc.is_some() {
+                kf.description = desc;
+            };
+        })
+    }
+
+    fn put_watch_only(&self, account: &WatchOnlyAccount) -> Result<(), KeystoreError> {
+        self.watch_only
+            .write()
+            .unwrap()
+            .insert(account.address, account.clone());
+
+        Ok(())
+    }
+
+    fn search_watch_only(&self, addr: &Address) -> Result<WatchOnlyAccount, KeystoreError> {
+        self.watch_only
+            .read()
+            .unwrap()
+            .get(addr)
+            .cloned()
+            .ok_or_else(|| KeystoreError::NotFound(addr.to_string()))
+    }
+
+    fn list_accounts(&self, show_hidden: bool) -> Result<Vec<AccountInfo>, KeystoreError> {
+        let mut accounts: Vec<AccountInfo> = self
+            .keyfiles
+            .read()
+            .unwrap()
+            .values()
+            .filter(|(_, kf)| kf.visible.is_none() || kf.visible.unwrap() || show_hidden)
+            .map(|(filename, kf)| {
+                let mut info = AccountInfo::from(kf.clone());
+                info.filename = filename.clone();
+                info
+            })
+            .collect();
+
+        accounts.extend(
+            self.watch_only
+                .read()
+                .unwrap()
+                .values()
+                .cloned()
+                .map(AccountInfo::from),
+        );
+
+        Ok(accounts)
+    }
+
+    fn search_by_address(&self, addr: &Address) -> Result<(AccountInfo, KeyFile), KeystoreError> {
+        match self.keyfiles.read().unwrap().get(addr) {
+            Some((filename, kf)) => {
+                let mut info = AccountInfo::from(kf.clone());
+                info.filename = filename.clone();
+
+                Ok((info, kf.clone()))
+            }
+            None => Err(KeystoreError::NotFound(addr.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::keyfile;
+
+    #[test]
+    fn should_put_and_search() {
+        let storage = MemoryStorage::new();
+        let kf = keyfile();
+
+        storage.put(&kf).unwrap();
+
+        let (info, found) = storage.search_by_address(&kf.address).unwrap();
+        assert_eq!(found, kf);
+        assert_eq!(info.name, "name");
+        assert!(info.filename.starts_with("UTC--"));
+    }
+
+    #[test]
+    fn should_hide_and_list() {
+        let storage = MemoryStorage::new();
+        let kf = keyfile();
+        storage.put(&kf).unwrap();
+
+        storage.hide(&kf.address).unwrap();
+        assert!(storage.list_accounts(false).unwrap().is_empty());
+        assert_eq!(storage.list_accounts(true).unwrap().len(), 1);
+
+        storage.unhide(&kf.address).unwrap();
+        assert_eq!(storage.list_accounts(false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_delete() {
+        let storage = MemoryStorage::new();
+        let kf = keyfile();
+        storage.put(&kf).unwrap();
+
+        storage.delete(&kf.address).unwrap();
+        assert!(storage.search_by_address(&kf.address).is_err());
+        assert!(storage.delete(&kf.address).is_err());
+    }
+
+    #[test]
+    fn should_reject_same_address_under_another_name() {
+        let storage = MemoryStorage::new();
+        let kf = keyfile();
+        storage.put_with_name(&kf, "first").unwrap();
+
+        assert!(storage.put_with_name(&kf, "second").is_err());
+        assert_eq!(
+            storage.search_by_address(&kf.address).unwrap().0.filename,
+            "first"
+        );
+    }
+}
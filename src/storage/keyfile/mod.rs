@@ -1,19 +1,23 @@
 //! # Storage for `KeyFiles`
 ///
-/// Provides 2 variants of storage:
+/// Provides 3 variants of storage:
 /// * backed with `db`
 /// * plain filesystem
+/// * in memory
 ///
 mod db;
 mod error;
 mod fs;
+mod memory;
 
 pub use self::db::DbStorage;
 pub use self::error::KeystoreError;
 pub use self::fs::FsStorage;
+pub use self::memory::MemoryStorage;
 use crate::core::Address;
 use crate::keystore::KeyFile;
 use crate::util;
+use serde::{Deserialize, Serialize};
 
 /// Short account info
 ///
@@ -38,6 +42,12 @@ pub struct AccountInfo {
     /// show if account hidden from 'normal' listing
     /// `normal` - not forcing to show hidden accounts
     pub is_hidden: bool,
+
+    /// shows whether account is held without private key
+    pub is_watch_only: bool,
+
+    /// User specified tags (watch-only accounts only)
+    pub tags: Vec<String>,
 }
 
 impl From<KeyFile> for AccountInfo {
@@ -63,6 +73,39 @@ impl From<KeyFile> for AccountInfo {
     }
 }
 
+/// Watch-only account, an address held without private key
+///
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchOnlyAccount {
+    /// Address of account
+    pub address: Address,
+
+    /// Optional name for account
+    #[serde(default)]
+    pub name: String,
+
+    /// Optional description for account
+    #[serde(default)]
+    pub description: String,
+
+    /// Optional tags for account
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl From<WatchOnlyAccount> for AccountInfo {
+    fn from(account: WatchOnlyAccount) -> Self {
+        AccountInfo {
+            address: account.address.to_string(),
+            name: account.name,
+            description: account.description,
+            is_watch_only: true,
+            tags: account.tags,
+            ..Default::default()
+        }
+    }
+}
+
 /// Storage for `KeyFiles`
 ///
 pub trait KeyfileStorage: Send + Sync {
@@ -72,7 +115,20 @@ pub trait KeyfileStorage: Send + Sync {
     ///
     ///  * kf - `KeyFile` to insert
     ///
-    fn put(&self, kf: &KeyFile) -> Result<(), KeystoreError>;
+    fn put(&self, kf: &KeyFile) -> Result<(), KeystoreError> {
+        self.put_with_name(kf, &generate_filename(&kf.uuid.to_string()))
+    }
+
+    /// Put `KeyFile` inside storage under specified filename.
+    /// Existing `KeyFile` for the same address can be replaced
+    /// only under its current filename.
+    ///
+    /// # Arguments:
+    ///
+    ///  * kf - `KeyFile` to insert
+    ///  * filename - filename, as created by `generate_filename`
+    ///
+    fn put_with_name(&self, kf: &KeyFile, filename: &str) -> Result<(), KeystoreError>;
 
     /// Delete `KeyFile` from storage for specified `Address`
     ///
@@ -113,7 +169,23 @@ pub trait KeyfileStorage: Send + Sync {
         desc: Option<String>,
     ) -> Result<(), KeystoreError>;
 
-    /// Lists info for `Keystore` files inside storage
+    /// Put watch-only account inside storage
+    ///
+    /// # Arguments:
+    ///
+    ///  * account - `WatchOnlyAccount` to insert
+    ///
+    fn put_watch_only(&self, account: &WatchOnlyAccount) -> Result<(), KeystoreError>;
+
+    /// Search of watch-only account by specified `Address`
+    ///
+    /// # Arguments:
+    ///
+    ///  * addr - target `Address`
+    ///
+    fn search_watch_only(&self, addr: &Address) -> Result<WatchOnlyAccount, KeystoreError>;
+
+    /// Lists info for `Keystore` files and watch-only accounts inside storage
     /// Can include hidden files if flag set.
     ///
     /// # Arguments
@@ -154,6 +226,17 @@ pub trait KeyfileStorage: Send + Sync {
             },
         }
     }
+
+    /// Check whether specified address is held
+    /// as watch-only account (without private key)
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - address to check
+    ///
+    fn is_watch_only(&self, addr: &Address) -> bool {
+        self.search_watch_only(addr).is_ok()
+    }
 }
 
 /// Creates filename for keystore file in format:
@@ -0,0 +1,217 @@
+//! # Accounts shared across chains
+//!
+//! `KeyFile` stays in the keystore of its home chain, other chains only
+//! refer to it. So passphrase or metadata changes apply to all of them at once.
+//!
+
+use super::{write_atomic, KeystoreError};
+use crate::core::Address;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Account enabled for chains other than its home chain
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountLink {
+    /// Chain which keystore holds `KeyFile`
+    pub chain: String,
+
+    /// Other chains, account is enabled for
+    pub linked: BTreeSet<String>,
+}
+
+/// Registry of cross-chain account links
+#[derive(Debug)]
+pub struct AccountLinks {
+    /// File to persist links, nothing is persisted if absent
+    path: Option<PathBuf>,
+
+    links: RwLock<BTreeMap<Address, AccountLink>>,
+}
+
+impl AccountLinks {
+    /// Open links stored in a JSON file, created on first change
+    ///
+    /// # Arguments:
+    ///
+    /// * path - path to links file
+    ///
+    pub fn new(path: PathBuf) -> Result<AccountLinks, KeystoreError> {
+        let links = if path.exists() {
+            serde_json::from_reader(File::open(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(AccountLinks {
+            path: Some(path),
+            links: RwLock::new(links),
+        })
+    }
+
+    /// Create links registry kept in memory only
+    pub fn in_memory() -> AccountLinks {
+        AccountLinks {
+            path: None,
+            links: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Find link of an account
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    ///
+    pub fn get(&self, addr: &Address) -> Option<AccountLink> {
+        self.links.read().unwrap().get(addr).cloned()
+    }
+
+    /// Home chain of an account enabled for specified chain by a link
+    ///
+    /// # Arguments:
+    ///
+    /// * chain - linked chain name
+    /// * addr - account address
+    ///
+    pub fn home_chain(&self, chain: &str, addr: &Address) -> Option<String> {
+        self.links
+            .read()
+            .unwrap()
+            .get(addr)
+            .filter(|link| link.linked.contains(chain))
+            .map(|link| link.chain.clone())
+    }
+
+    /// List accounts linked into specified chain, with their home chains
+    ///
+    /// # Arguments:
+    ///
+    /// * chain - linked chain name
+    ///
+    pub fn linked_to(&self, chain: &str) -> Vec<(Address, String)> {
+        self.links
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, link)| link.linked.contains(chain))
+            .map(|(addr, link)| (*addr, link.chain.clone()))
+            .collect()
+    }
+
+    /// Enable account of home chain for other chains
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    /// * home - chain which keystore holds `KeyFile`
+    /// * chains - chains to enable account for
+    ///
+    pub fn link(&self, addr: &Address, home: &str, chains: &[String]) -> Result<(), KeystoreError> {
+        let mut links = self.links.write().unwrap();
+        let link = links.entry(*addr).or_insert_with(|| AccountLink {
+            chain: home.to_string(),
+            linked: BTreeSet::new(),
+        });
+        if link.chain != home {
+            return Err(KeystoreError::StorageError(format!(
+                "Account {} is already shared from chain: {}",
+                addr, link.chain
+            )));
+        }
+        link.linked
+            .extend(chains.iter().filter(|c| *c != home).cloned());
+
+        self.save(&links)
+    }
+
+    /// Disable account for specified chains, dropping link if no chains left
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    /// * chains - chains to disable account for
+    ///
+    pub fn unlink(&self, addr: &Address, chains: &[String]) -> Result<(), KeystoreError> {
+        let mut links = self.links.write().unwrap();
+        let empty = match links.get_mut(addr) {
+            Some(link) => {
+                for c in chains {
+                    link.linked.remove(c);
+                }
+                link.linked.is_empty()
+            }
+            None => return Ok(()),
+        };
+        if empty {
+            links.remove(addr);
+        }
+
+        self.save(&links)
+    }
+
+    /// Drop all links of an account
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    ///
+    pub fn remove(&self, addr: &Address) -> Result<(), KeystoreError> {
+        let mut links = self.links.write().unwrap();
+        if links.remove(addr).is_none() {
+            return Ok(());
+        }
+
+        self.save(&links)
+    }
+
+    fn save(&self, links: &BTreeMap<Address, AccountLink>) -> Result<(), KeystoreError> {
+        match self.path {
+            Some(ref path) => {
+                write_atomic(path, &serde_json::to_vec_pretty(links)?).map_err(From::from)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_address as addr;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_link_and_unlink() {
+        let links = AccountLinks::in_memory();
+        let chains = vec!["etc".to_string(), "eth".to_string(), "morden".to_string()];
+
+        links.link(&addr(), "etc", &chains).unwrap();
+        assert_eq!(links.home_chain("eth", &addr()), Some("etc".to_string()));
+        assert_eq!(links.home_chain("etc", &addr()), None);
+        assert_eq!(links.linked_to("morden"), vec![(addr(), "etc".to_string())]);
+        assert!(links.link(&addr(), "eth", &chains).is_err());
+
+        links
+            .unlink(&addr(), &["eth".to_string(), "morden".to_string()])
+            .unwrap();
+        assert_eq!(links.get(&addr()), None);
+    }
+
+    #[test]
+    fn should_persist_links() {
+        let dir = TempDir::new("jade").unwrap();
+        let path = dir.path().join("links.json");
+
+        AccountLinks::new(path.clone())
+            .unwrap()
+            .link(&addr(), "etc", &["eth".to_string()])
+            .unwrap();
+
+        let links = AccountLinks::new(path).unwrap();
+        assert_eq!(links.home_chain("eth", &addr()), Some("etc".to_string()));
+    }
+}
@@ -0,0 +1,124 @@
+//! # Migration of `KeyFiles` between storage backends
+
+use super::{AccountInfo, KeyfileStorage, KeystoreError};
+use crate::core::Address;
+use serde::Serialize;
+
+/// Summary of `KeyFiles` migration for a single chain
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Chain name
+    pub chain: String,
+
+    /// Number of keyfiles copied into target storage
+    pub keyfiles: usize,
+
+    /// Number of watch-only accounts copied into target storage
+    pub watch_only: usize,
+
+    /// Number of accounts already present in target storage
+    pub skipped: usize,
+}
+
+/// Copy all keyfiles and watch-only accounts from one storage into another,
+/// keeping original filenames. Every copied record is read back and checked.
+/// Accounts already present in target storage are skipped.
+///
+/// # Arguments:
+///
+/// * `chain` - chain name, used for report
+/// * `from` - source storage
+/// * `to` - target storage
+///
+pub fn migrate(
+    chain: &str,
+    from: &dyn KeyfileStorage,
+    to: &dyn KeyfileStorage,
+) -> Result<MigrationReport, KeystoreError> {
+    let mut report = MigrationReport {
+        chain: chain.to_string(),
+        ..Default::default()
+    };
+
+    for info in from.list_accounts(true)? {
+        let addr = info
+            .address
+            .parse::<Address>()
+            .map_err(|e| KeystoreError::StorageError(format!("Invalid account address: {}", e)))?;
+
+        if to.search_by_address(&addr).is_ok() || to.is_watch_only(&addr) {
+            report.skipped += 1;
+            continue;
+        }
+
+        if info.is_watch_only {
+            let account = from.search_watch_only(&addr)?;
+            to.put_watch_only(&account)?;
+
+            if to.search_watch_only(&addr)? != account {
+                return Err(verification_error(chain, &info));
+            }
+            report.watch_only += 1;
+            continue;
+        }
+
+        let (_, kf) = from.search_by_address(&addr)?;
+        to.put_with_name(&kf, &info.filename)?;
+
+        let (copied_info, copied) = to.search_by_address(&addr)?;
+        if copied != kf || copied_info.filename != info.filename {
+            return Err(verification_error(chain, &info));
+        }
+        report.keyfiles += 1;
+    }
+
+    Ok(report)
+}
+
+fn verification_error(chain: &str, info: &AccountInfo) -> KeystoreError {
+    KeystoreError::StorageError(format!(
+        "Migrated account {} for chain {} doesn't match the source",
+        info.address, chain
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{DbStorage, FsStorage, WatchOnlyAccount};
+    use crate::tests::{keyfile, test_address};
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_migrate_fs_to_db() {
+        let fs_dir = TempDir::new("jade").unwrap();
+        let db_dir = TempDir::new("jade").unwrap();
+        let from = FsStorage::new(fs_dir.path());
+        let to = DbStorage::new(db_dir.path()).unwrap();
+
+        let mut kf = keyfile();
+        kf.visible = Some(false);
+        from.put_with_name(&kf, "UTC--2019-05-01T10-00-00Z--migrated")
+            .unwrap();
+        let watch_only = WatchOnlyAccount {
+            address: test_address(),
+            ..Default::default()
+        };
+        from.put_watch_only(&watch_only).unwrap();
+
+        let report = migrate("etc", &from, &to).unwrap();
+        assert_eq!(report.keyfiles, 1);
+        assert_eq!(report.watch_only, 1);
+        assert_eq!(report.skipped, 0);
+
+        let (info, copied) = to.search_by_address(&kf.address).unwrap();
+        assert_eq!(info.filename, "UTC--2019-05-01T10-00-00Z--migrated");
+        assert!(info.is_hidden);
+        assert_eq!(copied, kf);
+        assert!(to.is_watch_only(&watch_only.address));
+
+        let report = migrate("etc", &from, &to).unwrap();
+        assert_eq!(report.keyfiles, 0);
+        assert_eq!(report.skipped, 2);
+    }
+}
@@ -1,18 +1,41 @@
-//! # Storage for `KeyFiles` and `Contracts`
+//! # Storage for `KeyFiles`, `Contracts`, HD wallet seeds, watch-only `xpub` accounts
+//! and audit logs
 
+mod audit;
 mod contracts;
 mod keyfile;
+mod links;
+mod migration;
+mod seeds;
 mod storage_ctrl;
+mod trash;
+mod xpubs;
 
-pub use self::contracts::ContractStorage;
+pub use self::audit::{AuditEntry, AuditFilter, AuditLog, AuditRecord, AuditVerification};
+use self::contracts::import_contracts;
+pub use self::contracts::{
+    ContractStorage, DbContractStorage, FsContractStorage, MemoryContractStorage,
+};
 pub use self::keyfile::*;
+pub use self::links::AccountLinks;
+pub use self::migration::{migrate, MigrationReport};
+pub use self::seeds::{SeedAccount, SeedStorage};
 pub use self::storage_ctrl::StorageController;
+pub use self::trash::{TrashEntry, TrashStorage};
+pub use self::xpubs::{XpubAccount, XpubStorage};
 pub use self::KeystoreError;
 use std::boxed::Box;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+/// Suffix of temporary file, renamed to target name once fully written
+pub const TMP_SUFFIX: &str = ".tmp";
+
+/// Marker of contracts JSON files already imported into database storage
+const CONTRACTS_IMPORTED_MARKER: &str = ".imported";
+
 /// Base dir for internal data, all chain-related should be store in subdirectories
 #[derive(Debug, Clone)]
 pub struct Storages {
@@ -21,12 +44,14 @@ pub struct Storages {
 }
 
 /// Available storage types
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StorageType {
     /// Store keyfiles on filesystem (as files)
     Filesystem,
     /// Store keyfiles in RocksDB
     RocksDB,
+    /// Keep keyfiles in memory only, nothing is persisted
+    InMemory,
 }
 
 impl FromStr for StorageType {
@@ -36,8 +61,9 @@ impl FromStr for StorageType {
         match s.to_ascii_lowercase().as_str() {
             "filesystem" => Ok(StorageType::Filesystem),
             "rocksdb" => Ok(StorageType::RocksDB),
+            "memory" => Ok(StorageType::InMemory),
             other => Err(failure::format_err!(
-                "Unknown storage type {:?}, available types: ['rocksdb', 'filesystem']",
+                "Unknown storage type {:?}, available types: ['rocksdb', 'filesystem', 'memory']",
                 other
             )),
         }
@@ -90,6 +116,32 @@ pub fn build_path(base_path: &Path, chain: &str, folder: &str) -> PathBuf {
     path
 }
 
+/// Write file atomically: data goes into temporary file,
+/// which is flushed to disk and then renamed to target path
+///
+/// # Arguments:
+///
+/// * `path` - target path
+/// * `data` - file content
+///
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = File::create(&tmp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+
+    // Persist rename itself, not supported for directories on Windows
+    if let Some(dir) = path.parent() {
+        File::open(dir).and_then(|d| d.sync_all()).ok();
+    }
+
+    Ok(())
+}
+
 /// Creates specific type of `KeyFile` storage (database or filesystem)
 ///
 /// # Arguments:
@@ -115,24 +167,204 @@ where
                 )),
             }
         }
-        StorageType::Filesystem => Ok(Box::new(FsStorage::new(path))),
+        StorageType::Filesystem => {
+            fs::create_dir_all(&path)?;
+            Ok(Box::new(FsStorage::new(path)))
+        }
+        StorageType::InMemory => Ok(Box::new(MemoryStorage::new())),
     }
 }
 
-/// Creates specific type of `Contract` storage (database or filesystem)
+/// Creates specific type of `Contract` storage (database, filesystem or memory).
+/// Database storage imports contracts left as JSON files by filesystem storage
+/// on the first open.
 ///
 /// # Arguments:
 ///
 /// * `path` - path for `Contract` storage
 ///
-pub fn build_contract_storage<P>(path: P) -> Result<Box<ContractStorage>, KeystoreError>
+pub fn build_contract_storage<P>(
+    path: P,
+    storage_type: StorageType,
+) -> Result<Box<dyn ContractStorage>, KeystoreError>
+where
+    P: AsRef<Path>,
+{
+    match storage_type {
+        StorageType::RocksDB => {
+            let dir = path.as_ref().to_path_buf();
+            let db = match DbContractStorage::new(dir.join(".db")) {
+                Ok(db) => db,
+                Err(_) => {
+                    return Err(KeystoreError::StorageError(
+                        "Can't create database Contract storage".to_string(),
+                    ))
+                }
+            };
+
+            let marker = dir.join(CONTRACTS_IMPORTED_MARKER);
+            if !marker.exists() {
+                let imported =
+                    import_contracts(&FsContractStorage::new(dir), &db).map_err(|e| {
+                        KeystoreError::StorageError(format!("Can't import contracts: {}", e))
+                    })?;
+                if imported > 0 {
+                    log::info!("Imported {} contracts into database storage", imported);
+                }
+                fs::write(&marker, "")?;
+            }
+
+            Ok(Box::new(db))
+        }
+        StorageType::Filesystem => {
+            let mut p = PathBuf::new();
+            p.push(path);
+            fs::create_dir_all(&p)?;
+
+            Ok(Box::new(FsContractStorage::new(p)))
+        }
+        StorageType::InMemory => Ok(Box::new(MemoryContractStorage::default())),
+    }
+}
+
+/// Creates storage for deleted `KeyFiles`
+///
+/// # Arguments:
+///
+/// * `path` - path for `TrashEntry` storage
+///
+pub fn build_trash_storage<P>(
+    path: P,
+    storage_type: StorageType,
+) -> Result<Box<TrashStorage>, KeystoreError>
 where
     P: AsRef<Path>,
 {
-    // TODO: implement DB storage. Add conditional compilation.
+    if let StorageType::InMemory = storage_type {
+        return Ok(Box::new(TrashStorage::in_memory()));
+    }
+
     let mut p = PathBuf::new();
     p.push(path);
     fs::create_dir_all(&p)?;
 
-    Ok(Box::new(ContractStorage::new(p)))
+    Ok(Box::new(TrashStorage::new(p)))
+}
+
+/// Creates storage for HD wallet seeds
+///
+/// # Arguments:
+///
+/// * `path` - path for `SeedAccount` storage
+///
+pub fn build_seed_storage<P>(
+    path: P,
+    storage_type: StorageType,
+) -> Result<Box<SeedStorage>, KeystoreError>
+where
+    P: AsRef<Path>,
+{
+    if let StorageType::InMemory = storage_type {
+        return Ok(Box::new(SeedStorage::in_memory()));
+    }
+
+    let mut p = PathBuf::new();
+    p.push(path);
+    fs::create_dir_all(&p)?;
+
+    Ok(Box::new(SeedStorage::new(p)))
+}
+
+/// Creates storage for watch-only `xpub` account families
+///
+/// # Arguments:
+///
+/// * `path` - path for `XpubAccount` storage
+///
+pub fn build_xpub_storage<P>(
+    path: P,
+    storage_type: StorageType,
+) -> Result<Box<XpubStorage>, KeystoreError>
+where
+    P: AsRef<Path>,
+{
+    if let StorageType::InMemory = storage_type {
+        return Ok(Box::new(XpubStorage::in_memory()));
+    }
+
+    let mut p = PathBuf::new();
+    p.push(path);
+    fs::create_dir_all(&p)?;
+
+    Ok(Box::new(XpubStorage::new(p)))
+}
+
+/// Creates audit log of signing operations
+///
+/// # Arguments:
+///
+/// * `path` - path for audit log file
+///
+pub fn build_audit_log<P>(path: P, storage_type: StorageType) -> Result<AuditLog, KeystoreError>
+where
+    P: AsRef<Path>,
+{
+    if let StorageType::InMemory = storage_type {
+        return Ok(AuditLog::in_memory());
+    }
+
+    let p = path.as_ref().to_path_buf();
+    if let Some(dir) = p.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    AuditLog::new(p)
+}
+
+/// Creates registry of accounts shared across chains
+///
+/// # Arguments:
+///
+/// * `base_path` - base folder for storage
+///
+pub fn build_account_links(
+    base_path: &Path,
+    storage_type: StorageType,
+) -> Result<AccountLinks, KeystoreError> {
+    if let StorageType::InMemory = storage_type {
+        return Ok(AccountLinks::in_memory());
+    }
+
+    fs::create_dir_all(base_path)?;
+    AccountLinks::new(base_path.join("links.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{test_address, TEST_ADDRESS};
+    use serde_json::json;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_import_fs_contracts_into_db_once() {
+        let dir = TempDir::new("jade").unwrap();
+        let contract = json!({
+            "address": TEST_ADDRESS,
+            "name": "Token",
+            "abi": []
+        });
+        FsContractStorage::new(dir.path().to_path_buf())
+            .add(&contract)
+            .unwrap();
+
+        let storage = build_contract_storage(dir.path(), StorageType::RocksDB).unwrap();
+        assert_eq!(storage.get(&test_address()), Some(contract));
+
+        storage.delete(&test_address()).unwrap();
+        drop(storage);
+
+        let storage = build_contract_storage(dir.path(), StorageType::RocksDB).unwrap();
+        assert_eq!(storage.get(&test_address()), None);
+    }
 }
@@ -0,0 +1,173 @@
+//! # Storage for HD wallet seeds encrypted with a passphrase
+
+use super::{write_atomic, KeystoreError};
+use crate::keystore::CoreCrypto;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// HD wallet seed, encrypted the same way as a private key of `KeyFile`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SeedAccount {
+    /// UUID v4
+    pub id: Uuid,
+
+    /// User specified name
+    #[serde(default)]
+    pub name: String,
+
+    /// User specified description
+    #[serde(default)]
+    pub description: String,
+
+    /// Encrypted seed
+    pub crypto: CoreCrypto,
+}
+
+/// Storage for `SeedAccount`s, either filesystem or in memory
+#[derive(Debug)]
+pub struct SeedStorage {
+    backend: Backend,
+}
+
+#[derive(Debug)]
+enum Backend {
+    /// JSON files in a dir, one per seed
+    Dir(PathBuf),
+
+    /// Nothing is persisted
+    Memory(RwLock<BTreeMap<Uuid, SeedAccount>>),
+}
+
+impl SeedStorage {
+    /// Initialize new seed storage for a dir
+    pub fn new(dir: PathBuf) -> SeedStorage {
+        SeedStorage {
+            backend: Backend::Dir(dir),
+        }
+    }
+
+    /// Initialize new seed storage kept in memory only
+    pub fn in_memory() -> SeedStorage {
+        SeedStorage {
+            backend: Backend::Memory(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Add new seed to storage, overwrites existing entry with the same id
+    pub fn add(&self, seed: &SeedAccount) -> Result<(), KeystoreError> {
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let json = serde_json::to_vec_pretty(seed)?;
+                write_atomic(&SeedStorage::build_path(dir, &seed.id), &json)?;
+            }
+            Backend::Memory(ref seeds) => {
+                seeds.write().unwrap().insert(seed.id, seed.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get seed with specified id
+    pub fn get(&self, id: &Uuid) -> Result<SeedAccount, KeystoreError> {
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let path = SeedStorage::build_path(dir, id);
+                if !path.exists() {
+                    return Err(KeystoreError::NotFound(id.to_string()));
+                }
+
+                SeedStorage::read_json(&path)
+            }
+            Backend::Memory(ref seeds) => seeds
+                .read()
+                .unwrap()
+                .get(id)
+                .cloned()
+                .ok_or_else(|| KeystoreError::NotFound(id.to_string())),
+        }
+    }
+
+    /// List all available seeds
+    pub fn list(&self) -> Vec<SeedAccount> {
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let files = glob(&format!("{}/*.json", dir.to_str().unwrap())).unwrap();
+
+                files
+                    .filter_map(Result::ok)
+                    .filter_map(|p| SeedStorage::read_json(p.as_path()).ok())
+                    .collect()
+            }
+            Backend::Memory(ref seeds) => seeds.read().unwrap().values().cloned().collect(),
+        }
+    }
+
+    fn build_path(dir: &Path, id: &Uuid) -> PathBuf {
+        let mut path = dir.to_path_buf();
+        path.push(format!("{}.json", id));
+        path
+    }
+
+    fn read_json(path: &Path) -> Result<SeedAccount, KeystoreError> {
+        let f = File::open(path)?;
+        serde_json::from_reader(f).map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystore::{Kdf, KdfParams};
+    use tempdir::TempDir;
+
+    fn seed_account() -> SeedAccount {
+        let mut crypto = CoreCrypto {
+            kdf_params: KdfParams {
+                kdf: Kdf::from((8, 2, 1)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        crypto.encrypt_custom(&[7u8; 64], "1234567890", &mut rand::thread_rng());
+
+        SeedAccount {
+            id: Uuid::new_v4(),
+            name: "main".to_string(),
+            description: "".to_string(),
+            crypto,
+        }
+    }
+
+    #[test]
+    fn should_add_and_decrypt_seed() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = SeedStorage::new(dir.path().to_path_buf());
+        let seed = seed_account();
+
+        storage.add(&seed).unwrap();
+
+        assert_eq!(storage.list(), vec![seed.clone()]);
+        let stored = storage.get(&seed.id).unwrap();
+        assert_eq!(stored.crypto.decrypt("1234567890").unwrap(), vec![7u8; 64]);
+        assert!(stored.crypto.decrypt("0987654321").is_err());
+        assert!(storage.get(&Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn should_add_and_list_seed_in_memory() {
+        let storage = SeedStorage::in_memory();
+        let seed = seed_account();
+
+        storage.add(&seed).unwrap();
+
+        assert_eq!(storage.list(), vec![seed.clone()]);
+        assert_eq!(storage.get(&seed.id).unwrap(), seed);
+    }
+}
@@ -1,38 +1,63 @@
 use super::contracts::ContractStorage;
 use super::keyfile::KeystoreError;
-use super::{build_contract_storage, build_keyfile_storage, build_path, KeyfileStorage};
+use super::seeds::SeedStorage;
+use super::trash::TrashStorage;
+use super::xpubs::XpubStorage;
+use super::{
+    build_account_links, build_audit_log, build_contract_storage, build_keyfile_storage,
+    build_path, build_seed_storage, build_trash_storage, build_xpub_storage, migrate, AccountLinks,
+    AuditLog, KeyfileStorage, MigrationReport,
+};
+use crate::chains::{ChainInfo, ChainRegistry};
+use crate::core::Address;
 use crate::storage::StorageType;
 use std::collections::HashMap;
-use std::path::Path;
-
-const CHAIN_NAMES: &[&str; 9] = &[
-    "eth",
-    "morden",
-    "ropsten",
-    "rinkeby",
-    "rootstock-main",
-    "rootstock-test",
-    "kovan",
-    "etc",
-    "etc-morden",
-];
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// Controller to switch storage according to specified chain
+///
+/// Underlying storages are synchronized internally, so controller is shared
+/// between RPC handlers without a global lock. Read-modify-write operations
+/// over a single account are serialized with `account_lock`.
 pub struct StorageController {
+    base_path: PathBuf,
+    storage_type: StorageType,
+    chains: ChainRegistry,
     keyfile_storages: HashMap<String, Box<dyn KeyfileStorage>>,
-    contract_storages: HashMap<String, Box<ContractStorage>>,
+    trash_storages: HashMap<String, Box<TrashStorage>>,
+    contract_storages: HashMap<String, Box<dyn ContractStorage>>,
+    seed_storages: HashMap<String, Box<SeedStorage>>,
+    xpub_storages: HashMap<String, Box<XpubStorage>>,
+    audit_logs: HashMap<String, AuditLog>,
+    links: AccountLinks,
+    account_locks: Mutex<HashMap<(String, Address), Arc<Mutex<()>>>>,
 }
 
 impl StorageController {
     /// Create new `StorageController`
-    /// with a subfolders for
+    /// with a subfolders for each chain of the registry
     pub fn new<P: AsRef<Path>>(
         base_path: P,
         storage_type: StorageType,
+        chains: ChainRegistry,
     ) -> Result<StorageController, KeystoreError> {
-        let mut st = StorageController::default();
+        let mut st = StorageController {
+            base_path: base_path.as_ref().to_path_buf(),
+            storage_type,
+            chains,
+            keyfile_storages: HashMap::new(),
+            trash_storages: HashMap::new(),
+            contract_storages: HashMap::new(),
+            seed_storages: HashMap::new(),
+            xpub_storages: HashMap::new(),
+            audit_logs: HashMap::new(),
+            links: build_account_links(base_path.as_ref(), storage_type)?,
+            account_locks: Mutex::new(HashMap::new()),
+        };
 
-        for id in CHAIN_NAMES {
+        for chain in st.chains.list() {
+            let id = &chain.name;
             st.keyfile_storages.insert(
                 id.to_string(),
                 build_keyfile_storage(
@@ -40,15 +65,49 @@ impl StorageController {
                     storage_type,
                 )?,
             );
+            st.trash_storages.insert(
+                id.to_string(),
+                build_trash_storage(build_path(base_path.as_ref(), id, "trash"), storage_type)?,
+            );
             st.contract_storages.insert(
                 id.to_string(),
-                build_contract_storage(build_path(base_path.as_ref(), id, "contracts"))?,
+                build_contract_storage(
+                    build_path(base_path.as_ref(), id, "contracts"),
+                    storage_type,
+                )?,
+            );
+            st.seed_storages.insert(
+                id.to_string(),
+                build_seed_storage(build_path(base_path.as_ref(), id, "seeds"), storage_type)?,
+            );
+            st.xpub_storages.insert(
+                id.to_string(),
+                build_xpub_storage(build_path(base_path.as_ref(), id, "xpubs"), storage_type)?,
+            );
+            st.audit_logs.insert(
+                id.to_string(),
+                build_audit_log(
+                    build_path(base_path.as_ref(), id, "audit.log"),
+                    storage_type,
+                )?,
             );
         }
 
         Ok(st)
     }
 
+    /// Get registry of supported chains
+    pub fn chains(&self) -> &ChainRegistry {
+        &self.chains
+    }
+
+    /// Get description of specified chain
+    pub fn chain(&self, chain: &str) -> Result<&ChainInfo, KeystoreError> {
+        self.chains
+            .find(chain)
+            .ok_or_else(|| KeystoreError::StorageError(format!("Unknown chain: {}", chain)))
+    }
+
     /// Get `KeyFile` storage for specified chain
     pub fn get_keystore(&self, chain: &str) -> Result<&dyn KeyfileStorage, KeystoreError> {
         match self.keyfile_storages.get(chain) {
@@ -60,9 +119,93 @@ impl StorageController {
         }
     }
 
+    /// Get keystore holding `KeyFile` of an account available on specified chain,
+    /// either its own or keystore of a chain the account is shared from
+    ///
+    /// # Arguments:
+    ///
+    /// * `chain` - chain name
+    /// * `addr` - account address
+    ///
+    /// # Return:
+    ///
+    /// Tuple of home chain name and its keystore
+    ///
+    pub fn resolve_keystore(
+        &self,
+        chain: &str,
+        addr: &Address,
+    ) -> Result<(String, &dyn KeyfileStorage), KeystoreError> {
+        let home = self
+            .links
+            .home_chain(chain, addr)
+            .unwrap_or_else(|| chain.to_string());
+
+        Ok((home.clone(), self.get_keystore(&home)?))
+    }
+
+    /// Get registry of accounts shared across chains
+    pub fn links(&self) -> &AccountLinks {
+        &self.links
+    }
+
+    /// Get storage of deleted `KeyFile`s for specified chain
+    pub fn get_trash(&self, chain: &str) -> Result<&TrashStorage, KeystoreError> {
+        match self.trash_storages.get(chain) {
+            Some(st) => Ok(&st),
+            None => Err(KeystoreError::StorageError(format!(
+                "No storage for: {}",
+                chain
+            ))),
+        }
+    }
+
     /// Get `Contract` storage for specified chain
-    pub fn get_contracts(&self, chain: &str) -> Result<&ContractStorage, KeystoreError> {
+    pub fn get_contracts(&self, chain: &str) -> Result<&dyn ContractStorage, KeystoreError> {
         match self.contract_storages.get(chain) {
+            Some(st) => Ok(&**st),
+            None => Err(KeystoreError::StorageError(format!(
+                "No storage for: {}",
+                chain
+            ))),
+        }
+    }
+
+    /// Copy `KeyFile`s of all chains from current storage into storage of another type,
+    /// keeping original filenames. Copied records are verified against the source.
+    /// Deleted `KeyFile`s are kept on filesystem for both types, so they stay in place.
+    ///
+    /// # Arguments:
+    ///
+    /// * `target` - target storage type, `filesystem` or `rocksdb`
+    ///
+    pub fn migrate(&self, target: StorageType) -> Result<Vec<MigrationReport>, KeystoreError> {
+        match (self.storage_type, target) {
+            (StorageType::Filesystem, StorageType::RocksDB)
+            | (StorageType::RocksDB, StorageType::Filesystem) => {}
+            (from, to) => {
+                return Err(KeystoreError::StorageError(format!(
+                    "Can't migrate from {:?} to {:?} storage",
+                    from, to
+                )));
+            }
+        }
+
+        let mut reports = vec![];
+        for chain in self.chains.list() {
+            let id = &chain.name;
+            let from = self.get_keystore(id)?;
+            let to = build_keyfile_storage(build_path(&self.base_path, id, "keystore"), target)?;
+
+            reports.push(migrate(id, from, to.as_ref())?);
+        }
+
+        Ok(reports)
+    }
+
+    /// Get HD wallet seed storage for specified chain
+    pub fn get_seeds(&self, chain: &str) -> Result<&SeedStorage, KeystoreError> {
+        match self.seed_storages.get(chain) {
             Some(st) => Ok(&st),
             None => Err(KeystoreError::StorageError(format!(
                 "No storage for: {}",
@@ -70,13 +213,47 @@ impl StorageController {
             ))),
         }
     }
-}
 
-impl Default for StorageController {
-    fn default() -> Self {
-        StorageController {
-            keyfile_storages: HashMap::new(),
-            contract_storages: HashMap::new(),
+    /// Get watch-only `xpub` storage for specified chain
+    pub fn get_xpubs(&self, chain: &str) -> Result<&XpubStorage, KeystoreError> {
+        match self.xpub_storages.get(chain) {
+            Some(st) => Ok(&st),
+            None => Err(KeystoreError::StorageError(format!(
+                "No storage for: {}",
+                chain
+            ))),
         }
     }
+
+    /// Get audit log of signing operations for specified chain
+    pub fn get_audit_log(&self, chain: &str) -> Result<&AuditLog, KeystoreError> {
+        match self.audit_logs.get(chain) {
+            Some(log) => Ok(log),
+            None => Err(KeystoreError::StorageError(format!(
+                "No audit log for: {}",
+                chain
+            ))),
+        }
+    }
+
+    /// Get write lock for an account of specified chain.
+    /// Should be acquired after key derivation, to keep other requests
+    /// to the same account from waiting on KDF.
+    /// Locks not held by anyone are dropped, so registry doesn't grow
+    /// with every account ever touched.
+    ///
+    /// # Arguments:
+    ///
+    /// * `chain` - chain name
+    /// * `addr` - account address
+    ///
+    pub fn account_lock(&self, chain: &str, addr: &Address) -> Arc<Mutex<()>> {
+        let mut locks = self.account_locks.lock().unwrap();
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        Arc::clone(
+            locks
+                .entry((chain.to_string(), *addr))
+                .or_insert_with(|| Arc::new(Mutex::new(()))),
+        )
+    }
 }
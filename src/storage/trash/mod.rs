@@ -0,0 +1,245 @@
+//! # Storage for deleted `KeyFiles`
+//!
+//! Every deletion is kept as a separate entry, keyed by address and time of deletion,
+//! so an account deleted again after restore doesn't replace its earlier copy.
+//!
+
+use super::{write_atomic, KeystoreError};
+use crate::core::Address;
+use crate::keystore::KeyFile;
+use chrono::Utc;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Deleted `KeyFile` with its original filename
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// Time of deletion, milliseconds since Unix epoch
+    pub deleted_at: i64,
+
+    /// Original filename of `KeyFile`
+    pub filename: String,
+
+    /// Deleted `KeyFile`
+    pub keyfile: KeyFile,
+}
+
+/// Storage for `TrashEntry`s, either filesystem or in memory
+#[derive(Debug)]
+pub struct TrashStorage {
+    backend: Backend,
+}
+
+#[derive(Debug)]
+enum Backend {
+    /// JSON files in a dir, one per entry
+    Dir(PathBuf),
+
+    /// Nothing is persisted
+    Memory(RwLock<BTreeMap<(Address, i64), TrashEntry>>),
+}
+
+impl TrashStorage {
+    /// Initialize new trash storage for a dir
+    pub fn new(dir: PathBuf) -> TrashStorage {
+        TrashStorage {
+            backend: Backend::Dir(dir),
+        }
+    }
+
+    /// Initialize new trash storage kept in memory only
+    pub fn in_memory() -> TrashStorage {
+        TrashStorage {
+            backend: Backend::Memory(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Add deleted `KeyFile`, keeping earlier deleted copies of the same account.
+    /// Time of deletion is later than of any existing copy, even if clock went back.
+    ///
+    /// # Arguments:
+    ///
+    /// * kf - deleted `KeyFile`
+    /// * filename - original filename
+    ///
+    pub fn put(&self, kf: &KeyFile, filename: &str) -> Result<TrashEntry, KeystoreError> {
+        let now = Utc::now().timestamp_millis();
+        let deleted_at = match self.latest(&kf.address) {
+            Ok(prev) => cmp::max(now, prev.deleted_at + 1),
+            Err(_) => now,
+        };
+        let entry = TrashEntry {
+            deleted_at,
+            filename: filename.to_string(),
+            keyfile: kf.clone(),
+        };
+
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let json = serde_json::to_vec_pretty(&entry)?;
+                write_atomic(
+                    &TrashStorage::build_path(dir, &kf.address, deleted_at),
+                    &json,
+                )?;
+            }
+            Backend::Memory(ref entries) => {
+                entries
+                    .write()
+                    .unwrap()
+                    .insert((kf.address, deleted_at), entry.clone());
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// Find deleted copies of an account, oldest first
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    ///
+    pub fn find(&self, addr: &Address) -> Vec<TrashEntry> {
+        let mut res: Vec<TrashEntry> = match self.backend {
+            Backend::Dir(ref dir) => {
+                TrashStorage::read_dir(&format!("{}/{}--*.json", dir.to_str().unwrap(), addr))
+            }
+            Backend::Memory(ref entries) => entries
+                .read()
+                .unwrap()
+                .values()
+                .filter(|e| e.keyfile.address == *addr)
+                .cloned()
+                .collect(),
+        };
+        res.sort_by_key(|e| e.deleted_at);
+        res
+    }
+
+    /// Get the most recently deleted copy of an account
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    ///
+    pub fn latest(&self, addr: &Address) -> Result<TrashEntry, KeystoreError> {
+        self.find(addr)
+            .pop()
+            .ok_or_else(|| KeystoreError::NotFound(addr.to_string()))
+    }
+
+    /// List all deleted copies of all accounts, ordered by address and time of deletion
+    pub fn list(&self) -> Vec<TrashEntry> {
+        let mut res: Vec<TrashEntry> = match self.backend {
+            Backend::Dir(ref dir) => {
+                TrashStorage::read_dir(&format!("{}/*.json", dir.to_str().unwrap()))
+            }
+            Backend::Memory(ref entries) => entries.read().unwrap().values().cloned().collect(),
+        };
+        res.sort_by_key(|e| (e.keyfile.address, e.deleted_at));
+        res
+    }
+
+    /// Remove a deleted copy of an account
+    ///
+    /// # Arguments:
+    ///
+    /// * addr - account address
+    /// * deleted_at - time of deletion of the copy
+    ///
+    pub fn remove(&self, addr: &Address, deleted_at: i64) -> Result<(), KeystoreError> {
+        let found = match self.backend {
+            Backend::Dir(ref dir) => {
+                let path = TrashStorage::build_path(dir, addr, deleted_at);
+                if path.exists() {
+                    fs::remove_file(path)?;
+                    true
+                } else {
+                    false
+                }
+            }
+            Backend::Memory(ref entries) => entries
+                .write()
+                .unwrap()
+                .remove(&(*addr, deleted_at))
+                .is_some(),
+        };
+
+        if found {
+            Ok(())
+        } else {
+            Err(KeystoreError::NotFound(format!(
+                "{} at {}",
+                addr, deleted_at
+            )))
+        }
+    }
+
+    fn build_path(dir: &Path, addr: &Address, deleted_at: i64) -> PathBuf {
+        let mut path = dir.to_path_buf();
+        path.push(format!("{}--{}.json", addr, deleted_at));
+        path
+    }
+
+    fn read_dir(pattern: &str) -> Vec<TrashEntry> {
+        glob(pattern)
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter_map(|p| TrashStorage::read_json(p.as_path()).ok())
+            .collect()
+    }
+
+    fn read_json(path: &Path) -> Result<TrashEntry, KeystoreError> {
+        let f = File::open(path)?;
+        serde_json::from_reader(f).map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::keyfile;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_keep_every_deleted_copy() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = TrashStorage::new(dir.path().to_path_buf());
+        let kf = keyfile();
+        let mut renamed = kf.clone();
+        renamed.name = Some("renamed".to_string());
+
+        let first = storage.put(&kf, "first").unwrap();
+        let second = storage.put(&renamed, "second").unwrap();
+
+        assert!(second.deleted_at > first.deleted_at);
+        assert_eq!(storage.list(), vec![first.clone(), second.clone()]);
+        assert_eq!(storage.latest(&kf.address).unwrap(), second);
+
+        storage.remove(&kf.address, second.deleted_at).unwrap();
+        assert_eq!(storage.latest(&kf.address).unwrap(), first);
+        assert!(storage.remove(&kf.address, second.deleted_at).is_err());
+    }
+
+    #[test]
+    fn should_keep_every_deleted_copy_in_memory() {
+        let storage = TrashStorage::in_memory();
+        let kf = keyfile();
+
+        let first = storage.put(&kf, "first").unwrap();
+        let second = storage.put(&kf, "second").unwrap();
+
+        assert_eq!(
+            storage.find(&kf.address),
+            vec![first.clone(), second.clone()]
+        );
+        storage.remove(&kf.address, first.deleted_at).unwrap();
+        assert_eq!(storage.list(), vec![second]);
+    }
+}
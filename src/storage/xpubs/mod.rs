@@ -0,0 +1,159 @@
+//! # Storage for watch-only `xpub` account families
+
+use super::{write_atomic, KeystoreError};
+use glob::glob;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Watch-only account family backed by an extended public key
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct XpubAccount {
+    /// Base58 encoded extended public key
+    pub xpub: String,
+
+    /// User specified name
+    #[serde(default)]
+    pub name: String,
+
+    /// User specified description
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Storage for `XpubAccount`s, either filesystem or in memory
+#[derive(Debug)]
+pub struct XpubStorage {
+    backend: Backend,
+}
+
+#[derive(Debug)]
+enum Backend {
+    /// JSON files in a dir, one per `xpub`
+    Dir(PathBuf),
+
+    /// Nothing is persisted
+    Memory(RwLock<BTreeMap<String, XpubAccount>>),
+}
+
+impl XpubStorage {
+    /// Initialize new xpub storage for a dir
+    pub fn new(dir: PathBuf) -> XpubStorage {
+        XpubStorage {
+            backend: Backend::Dir(dir),
+        }
+    }
+
+    /// Initialize new xpub storage kept in memory only
+    pub fn in_memory() -> XpubStorage {
+        XpubStorage {
+            backend: Backend::Memory(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Add new watch-only account family to storage,
+    /// overwrites existing entry for the same `xpub`
+    pub fn add(&self, account: &XpubAccount) -> Result<(), KeystoreError> {
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let json = serde_json::to_vec_pretty(account)?;
+                write_atomic(&XpubStorage::build_path(dir, &account.xpub), &json)?;
+            }
+            Backend::Memory(ref accounts) => {
+                accounts
+                    .write()
+                    .unwrap()
+                    .insert(account.xpub.clone(), account.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get watch-only account family for specified `xpub`
+    pub fn get(&self, xpub: &str) -> Result<XpubAccount, KeystoreError> {
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let path = XpubStorage::build_path(dir, xpub);
+                if !path.exists() {
+                    return Err(KeystoreError::NotFound(xpub.to_string()));
+                }
+
+                XpubStorage::read_json(&path)
+            }
+            Backend::Memory(ref accounts) => accounts
+                .read()
+                .unwrap()
+                .get(xpub)
+                .cloned()
+                .ok_or_else(|| KeystoreError::NotFound(xpub.to_string())),
+        }
+    }
+
+    /// List all available watch-only account families
+    pub fn list(&self) -> Vec<XpubAccount> {
+        match self.backend {
+            Backend::Dir(ref dir) => {
+                let files = glob(&format!("{}/*.json", dir.to_str().unwrap())).unwrap();
+
+                files
+                    .filter_map(Result::ok)
+                    .filter_map(|p| XpubStorage::read_json(p.as_path()).ok())
+                    .collect()
+            }
+            Backend::Memory(ref accounts) => accounts.read().unwrap().values().cloned().collect(),
+        }
+    }
+
+    fn build_path(dir: &Path, xpub: &str) -> PathBuf {
+        let mut path = dir.to_path_buf();
+        path.push(format!("{}.json", xpub));
+        path
+    }
+
+    fn read_json(path: &Path) -> Result<XpubAccount, KeystoreError> {
+        let f = File::open(path)?;
+        serde_json::from_reader(f).map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn should_add_and_list_xpub() {
+        let dir = TempDir::new("jade").unwrap();
+        let storage = XpubStorage::new(dir.path().to_path_buf());
+        let account = XpubAccount {
+            xpub: "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj".to_string(),
+            name: "receive".to_string(),
+            description: "".to_string(),
+        };
+
+        storage.add(&account).unwrap();
+
+        assert_eq!(storage.list(), vec![account.clone()]);
+        assert_eq!(storage.get(&account.xpub).unwrap(), account);
+        assert!(storage.get("xpub_missing").is_err());
+    }
+
+    #[test]
+    fn should_add_and_list_xpub_in_memory() {
+        let storage = XpubStorage::in_memory();
+        let account = XpubAccount {
+            xpub: "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj".to_string(),
+            name: "receive".to_string(),
+            description: "".to_string(),
+        };
+
+        storage.add(&account).unwrap();
+
+        assert_eq!(storage.list(), vec![account.clone()]);
+        assert_eq!(storage.get(&account.xpub).unwrap(), account);
+    }
+}
@@ -14,17 +14,6 @@ use std::mem::transmute;
 
 static HEX_CHARS: &[u8] = b"0123456789abcdef";
 
-const ETH: &str = "eth";
-const MORDEN: &str = "morden";
-const ROPSTEN: &str = "ropsten";
-const RINKEBY: &str = "rinkeby";
-const ROOTSTOCK_MAINNET: &str = "rootstock-main";
-const ROOTSTOCK_TESTNET: &str = "rootstock-test";
-const KOVAN: &str = "kovan";
-const ETC: &str = "etc";
-const MAINNET: &str = "mainnet";
-const ETC_MORDEN: &str = "etc-morden";
-
 /// Convert `self` into hex string
 pub trait ToHex {
     /// converts to hex
@@ -50,46 +39,6 @@ impl ToHex for u64 {
     }
 }
 
-/// Get chain name by chain id
-///
-/// # Arguments:
-/// * `id` - target chain id
-///
-pub fn to_chain_name(id: u8) -> Option<&'static str> {
-    match id {
-        1 => Some(ETH),
-        2 => Some(MORDEN),
-        3 => Some(ROPSTEN),
-        4 => Some(RINKEBY),
-        30 => Some(ROOTSTOCK_MAINNET),
-        31 => Some(ROOTSTOCK_TESTNET),
-        42 => Some(KOVAN),
-        61 => Some(ETC),
-        62 => Some(ETC_MORDEN),
-        _ => None,
-    }
-}
-
-/// Get chain id by chain name
-///
-/// # Arguments:
-/// * `name` - target chain name
-///
-pub fn to_chain_id(name: &str) -> Option<u8> {
-    match name.to_lowercase().as_str() {
-        ETH => Some(1),
-        MORDEN => Some(2),
-        ROPSTEN => Some(3),
-        RINKEBY => Some(4),
-        ROOTSTOCK_MAINNET => Some(30),
-        ROOTSTOCK_TESTNET => Some(31),
-        KOVAN => Some(42),
-        ETC | MAINNET => Some(61),
-        ETC_MORDEN => Some(62),
-        _ => None,
-    }
-}
-
 /// Convert byte array into `u64`
 ///
 /// # Arguments
@@ -167,6 +116,59 @@ pub fn to_even_str(data: &str) -> String {
     v
 }
 
+/// Format decimal integer as a number of units with specified decimals,
+/// i.e. `wei` as `ether` for 18 decimals. Trailing zeros of fraction are trimmed.
+///
+/// # Arguments
+///
+/// * `value` - decimal digits of integer value
+/// * `decimals` - number of decimals of unit
+///
+pub fn format_units(value: &str, decimals: usize) -> String {
+    let value = value.trim_start_matches('0');
+    let padded = format!("{:0>width$}", value, width = decimals + 1);
+    let (int, frac) = padded.split_at(padded.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+
+    if frac.is_empty() {
+        int.to_string()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+/// Parse decimal number of units into integer number of smallest units,
+/// i.e. `ether` into `wei` for 18 decimals
+///
+/// # Arguments
+///
+/// * `value` - decimal number, like `1.5`
+/// * `decimals` - number of decimals of unit
+///
+pub fn parse_units(value: &str, decimals: usize) -> Option<String> {
+    let (int, frac) = match value.find('.') {
+        Some(i) => (&value[..i], &value[i + 1..]),
+        None => (value, ""),
+    };
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (int.is_empty() && frac.is_empty()) || !is_digits(int) || !is_digits(frac) {
+        return None;
+    }
+
+    let frac = frac.trim_end_matches('0');
+    if frac.len() > decimals {
+        return None;
+    }
+
+    let digits = format!("{}{:0<width$}", int, frac, width = decimals);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        Some("0".to_string())
+    } else {
+        Some(digits.to_string())
+    }
+}
+
 /// Trim all high zero bytes
 ///
 /// # Arguments
@@ -286,6 +288,35 @@ mod tests {
     use super::*;
     use crate::tests::*;
 
+    #[test]
+    fn should_parse_units() {
+        assert_eq!(
+            parse_units("1", 18),
+            Some("1000000000000000000".to_string())
+        );
+        assert_eq!(
+            parse_units("1.50", 18),
+            Some("1500000000000000000".to_string())
+        );
+        assert_eq!(parse_units(".5", 1), Some("5".to_string()));
+        assert_eq!(parse_units("0.0", 6), Some("0".to_string()));
+        assert_eq!(parse_units("42", 0), Some("42".to_string()));
+        assert_eq!(parse_units("0.001", 2), None);
+        assert_eq!(parse_units("1e18", 18), None);
+        assert_eq!(parse_units("-1", 18), None);
+        assert_eq!(parse_units(".", 18), None);
+    }
+
+    #[test]
+    fn should_format_units() {
+        assert_eq!(format_units("0", 18), "0");
+        assert_eq!(format_units("1000000000000000000", 18), "1");
+        assert_eq!(format_units("1500000000000000000", 18), "1.5");
+        assert_eq!(format_units("21000000000000", 18), "0.000021");
+        assert_eq!(format_units("20000000000", 9), "20");
+        assert_eq!(format_units("123", 0), "123");
+    }
+
     #[test]
     fn should_convert_zero_string_into_16bytes() {
         assert_eq!(to_16bytes("00000000000000000000000000000000"), [0u8; 16]);
@@ -447,36 +478,4 @@ mod tests {
 
         assert!(re.is_match(&timestamp()));
     }
-
-    #[test]
-    fn should_convert_to_chain_id() {
-        assert_eq!(to_chain_id("eth"), Some(1));
-        assert_eq!(to_chain_id("morden"), Some(2));
-        assert_eq!(to_chain_id("ropsten"), Some(3));
-        assert_eq!(to_chain_id("rinkeby"), Some(4));
-        assert_eq!(to_chain_id("rootstock-main"), Some(30));
-        assert_eq!(to_chain_id("rootstock-test"), Some(31));
-        assert_eq!(to_chain_id("kovan"), Some(42));
-        assert_eq!(to_chain_id("etc"), Some(61));
-        assert_eq!(to_chain_id("mainnet"), Some(61));
-        assert_eq!(to_chain_id("etc-morden"), Some(62));
-
-        assert_eq!(to_chain_id("eTc"), Some(61));
-        assert_eq!(to_chain_id("ecccc"), None);
-    }
-
-    #[test]
-    fn should_convert_to_chain_name() {
-        assert_eq!(to_chain_name(1), Some(ETH));
-        assert_eq!(to_chain_name(2), Some(MORDEN));
-        assert_eq!(to_chain_name(3), Some(ROPSTEN));
-        assert_eq!(to_chain_name(4), Some(RINKEBY));
-        assert_eq!(to_chain_name(30), Some(ROOTSTOCK_MAINNET));
-        assert_eq!(to_chain_name(31), Some(ROOTSTOCK_TESTNET));
-        assert_eq!(to_chain_name(42), Some(KOVAN));
-        assert_eq!(to_chain_name(61), Some(ETC));
-        assert_eq!(to_chain_name(62), Some(ETC_MORDEN));
-
-        assert_eq!(to_chain_name(100), None);
-    }
 }